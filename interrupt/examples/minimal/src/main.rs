@@ -17,9 +17,8 @@ extern crate ruspiro_allocator;
 extern crate ruspiro_boot;
 extern crate ruspiro_interrupt;
 
-use alloc::boxed::Box;
 use ruspiro_boot::{come_alive_with, run_with};
-use ruspiro_interrupt::{self as irq, isr_channel, IrqHandler};
+use ruspiro_interrupt::{self as irq, isr_channel, IrqHandler, IsrSender};
 use ruspiro_mmio_register::define_mmio_register;
 use ruspiro_mmu as mmu;
 
@@ -50,8 +49,8 @@ fn alive(core: u32) {
   irq::enable_interrupts();
 
   // now create the ISR channel and register the same with the interrupt
-  let (timer_tx, timer_rx) = isr_channel();
-  irq::activate(irq::Interrupt::SystemTimer1, Some(timer_tx.clone()));
+  let (timer_tx, timer_rx) = isr_channel::<()>();
+  irq::activate(irq::Interrupt::SystemTimer1, timer_tx.clone());
 
   loop {
     // wait for the interrupt to send stuff through the channel and lit a led
@@ -73,14 +72,14 @@ fn running(_core: u32) -> ! {
 
 // provide the interrupt handler implementation for a specific interrupt
 #[IrqHandler(SystemTimer1)]
-fn isr_system_timer(channel: Option<IsrSender<Box<dyn Any>>>) {
+fn isr_system_timer(channel: Option<IsrSender<()>>) {
   // as soon as the interupt was raised we need to acknowledge the same
   // as we could configure up to 4 compare match values we need to check
   // which one actually raised this IRQ. We only deal with match value 1 here
   if SYS_TIMERCS::Register.read(SYS_TIMERCS::M1) == 1 {
     SYS_TIMERCS::Register.write_value(SYS_TIMERCS::M1::MATCH);
     // in case of a channel being present just send an empty message
-    channel.map(|tx| tx.send(Box::new(())));
+    channel.map(|tx| tx.send(()));
     // once we have received the timer interrupt update the match value
     // to trigger the interrupt again
     // set the match value to the current free-running counter + some delta
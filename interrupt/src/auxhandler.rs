@@ -7,69 +7,64 @@
 //! # Aux interrupt line handler
 //!
 //! The Aux interrupt line is shared between Uart1, Spi1 and Spi2. This handler branches to the specific handler
-//! implementation based on the interrupt source.
+//! implementation based on the interrupt source indicated by the AUX interrupt status register.
 //!
 
 use super::IsrChannel;
-use core::{cell::RefCell};
+use core::sync::atomic::AtomicU32;
+use crate::{
+  sharedline::{SharedLine, SharedSource},
+  Interrupt,
+};
+#[cfg(not(feature = "sim"))]
 use ruspiro_mmio_register::define_mmio_register;
 
+#[cfg(feature = "sim")]
+use crate::interface::aux_irq_status;
+
 #[cfg(feature = "pi3")]
+#[cfg_attr(feature = "sim", allow(dead_code))]
 const PERIPHERAL_BASE: usize = 0x0_3F00_0000;
 #[cfg(feature = "pi4_low")]
+#[cfg_attr(feature = "sim", allow(dead_code))]
 const PERIPHERAL_BASE: usize = 0x0_FE00_0000;
 #[cfg(feature = "pi4_high")]
+#[cfg_attr(feature = "sim", allow(dead_code))]
 const PERIPHERAL_BASE: usize = 0x4_7E00_0000;
 
+/// The devices sharing the Aux interrupt line. The value is the index of the device within the sources of the line.
 pub enum AuxDevice {
   Uart1,
   Spi1,
   Spi2,
 }
 
-pub(crate) fn set_aux_isrsender(aux: AuxDevice, channel: IsrChannel) {
-  if let Some(channel) = channel {
-    match aux {
-      AuxDevice::Uart1 => AUXISRSENDER.uart1.borrow_mut().replace(channel),
-      AuxDevice::Spi1 => AUXISRSENDER.spi1.borrow_mut().replace(channel),
-      AuxDevice::Spi2 => AUXISRSENDER.spi2.borrow_mut().replace(channel),
-    };
-  }
-}
-
-#[allow(improper_ctypes_definitions)]
-pub(crate) extern "C" fn aux_handler(_: IsrChannel) {
-  // special Aux handling, as one IRQ line shares interrupts between Uart1, SPI1 and SPI2
-  if AUX_IRQ::Register.read(AUX_IRQ::UART1) == 1 {
-    let channel = AUXISRSENDER.uart1.borrow().clone();
-    crate::__irq_handler__Aux_Uart1(channel);
-  }
+/// The Aux interrupt line shared between the devices
+pub(crate) static AUX_LINE: SharedLine = SharedLine {
+  irq: Interrupt::Aux,
+  status: aux_irq_status,
+  acknowledge: None,
+  disable: None,
+  line_handler: None,
+  sources: &AUX_SOURCES,
+  active: AtomicU32::new(0),
+};
 
-  if AUX_IRQ::Register.read(AUX_IRQ::SPI1) == 1 {
-    let channel = AUXISRSENDER.spi1.borrow().clone();
-    crate::__irq_handler__Aux_Spi1(channel);
-  }
+static AUX_SOURCES: [SharedSource; 3] = ruspiro_interrupt_macros::shared_sources!(Aux);
 
-  if AUX_IRQ::Register.read(AUX_IRQ::SPI2) == 1 {
-    let channel = AUXISRSENDER.spi2.borrow().clone();
-    crate::__irq_handler__Aux_Spi2(channel);
-  }
+#[allow(improper_ctypes_definitions)]
+pub(crate) extern "C" fn aux_handler(channel: &IsrChannel) {
+  AUX_LINE.dispatch(channel);
 }
 
-struct AuxIsrSender {
-  uart1: RefCell<IsrChannel>,
-  spi1: RefCell<IsrChannel>,
-  spi2: RefCell<IsrChannel>,
+/// Read the AUX interrupt status register indicating the devices having a pending interrupt. The bits of the devices
+/// are in the order of their sources.
+#[cfg(not(feature = "sim"))]
+fn aux_irq_status() -> u32 {
+  AUX_IRQ::Register.get()
 }
 
-unsafe impl Sync for AuxIsrSender {}
-
-static AUXISRSENDER: AuxIsrSender = AuxIsrSender {
-  uart1: RefCell::new(None),
-  spi1: RefCell::new(None),
-  spi2: RefCell::new(None),
-};
-
+#[cfg(not(feature = "sim"))]
 define_mmio_register! [
     AUX_IRQ<ReadWrite<u32>@(PERIPHERAL_BASE + 0x0021_5000)> {
         SPI2    OFFSET(2),
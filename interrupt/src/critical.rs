@@ -0,0 +1,159 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # Critical sections
+//!
+//! A critical section is a piece of code that shall not be interrupted on the current core. The [InterruptGuard]
+//! masks IRQ's and FIQ's when created and restores the previous mask state when dropped. Unlike calling
+//! [disable_interrupts] and [enable_interrupts] the critical sections could be nested. Leaving an inner critical section
+//! keeps the interrupts masked until the outer one is left.
+//!
+//! ```no_run
+//! # use ruspiro_interrupt::*;
+//! let value = critical_section(|_cs| {
+//!   // IRQ's and FIQ's are masked here
+//!   critical_section(|_cs| {
+//!     // nested critical section
+//!   });
+//!   // still masked
+//!   42
+//! });
+//! // the interrupts are enabled again if they have been enabled before the critical section
+//! ```
+//!
+//! [disable_interrupts]: super::disable_interrupts
+//! [enable_interrupts]: super::enable_interrupts
+
+use core::marker::PhantomData;
+
+use crate::interface::{self, DAIF_FIQ, DAIF_IRQ};
+
+/// Masks IRQ's and FIQ's on the current core while it exists. The mask state active when creating the guard is restored
+/// when it is dropped. The guard is bound to the core it has been created on and can not be send to another one.
+pub struct InterruptGuard {
+  daif: u64,
+  _not_send: PhantomData<*const ()>,
+}
+
+impl InterruptGuard {
+  /// Save the current mask state and mask IRQ's and FIQ's.
+  pub fn new() -> Self {
+    Self {
+      daif: mask_interrupts(),
+      _not_send: PhantomData,
+    }
+  }
+}
+
+impl Default for InterruptGuard {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Drop for InterruptGuard {
+  fn drop(&mut self) {
+    restore_interrupts(self.daif);
+  }
+}
+
+/// The token passed to the closure executed by [critical_section]. It proves that the code is running inside a critical
+/// section and can not outlive it.
+pub struct CriticalSection<'cs> {
+  _lifetime: PhantomData<&'cs ()>,
+}
+
+/// Execute the given closure with IRQ's and FIQ's masked on the current core. The mask state is restored once the
+/// closure returns, so critical sections could be nested.
+pub fn critical_section<F, R>(f: F) -> R
+where
+  F: FnOnce(&CriticalSection) -> R,
+{
+  let _guard = InterruptGuard::new();
+  f(&CriticalSection {
+    _lifetime: PhantomData,
+  })
+}
+
+/// Mask IRQ's and FIQ's on the current core and return the previous content of the ``DAIF`` register
+fn mask_interrupts() -> u64 {
+  let daif = interface::daif();
+  interface::disable_irq();
+  interface::disable_fiq();
+  daif
+}
+
+/// Restore the IRQ and FIQ mask bits from the given content of the ``DAIF`` register. The other bits are kept as they
+/// are.
+fn restore_interrupts(daif: u64) {
+  let current = interface::daif() & !(DAIF_IRQ | DAIF_FIQ);
+  interface::set_daif(current | (daif & (DAIF_IRQ | DAIF_FIQ)));
+}
+
+/// Check whether IRQ's and FIQ's are both enabled on the current core.
+pub fn are_interrupts_enabled() -> bool {
+  interface::daif() & (DAIF_IRQ | DAIF_FIQ) == 0
+}
+
+/// Check whether IRQ's are enabled on the current core.
+pub fn is_irq_enabled() -> bool {
+  interface::daif() & DAIF_IRQ == 0
+}
+
+/// Check whether FIQ's are enabled on the current core.
+pub fn is_fiq_enabled() -> bool {
+  interface::daif() & DAIF_FIQ == 0
+}
+
+/// The implementation of the [critical-section](https://crates.io/crates/critical-section) crate with the
+/// `critical-section-impl` feature. The interrupts are masked on the current core and a spinlock ensures that only one
+/// core at a time is inside a critical section. Nested critical sections on the core holding the lock do not wait for
+/// the lock again. As the lock is an atomic the MMU need to be configured before entering the first critical section.
+#[cfg(feature = "critical-section-impl")]
+mod critical_section_impl {
+  use super::{mask_interrupts, restore_interrupts};
+  use crate::interface;
+  use core::sync::atomic::{AtomicUsize, Ordering};
+  use critical_section::RawRestoreState;
+
+  /// The value of the lock owner if no core is inside a critical section
+  const NO_OWNER: usize = usize::MAX;
+  /// The bit of the restore state indicating that the critical section has taken the lock. The bits of the ``DAIF``
+  /// register do not overlap with it.
+  const LOCK_TAKEN: u64 = 1 << 63;
+
+  /// The core inside the critical section
+  static LOCK_OWNER: AtomicUsize = AtomicUsize::new(NO_OWNER);
+
+  struct RusPiRoCriticalSection;
+  critical_section::set_impl!(RusPiRoCriticalSection);
+
+  unsafe impl critical_section::Impl for RusPiRoCriticalSection {
+    unsafe fn acquire() -> RawRestoreState {
+      let daif = mask_interrupts();
+      let core = interface::core_id();
+      // a nested critical section on the core already holding the lock
+      if LOCK_OWNER.load(Ordering::Relaxed) == core {
+        return daif;
+      }
+      while LOCK_OWNER
+        .compare_exchange_weak(NO_OWNER, core, Ordering::Acquire, Ordering::Relaxed)
+        .is_err()
+      {
+        core::hint::spin_loop();
+      }
+      daif | LOCK_TAKEN
+    }
+
+    unsafe fn release(state: RawRestoreState) {
+      if state & LOCK_TAKEN != 0 {
+        LOCK_OWNER.store(NO_OWNER, Ordering::Release);
+      }
+      restore_interrupts(state);
+    }
+  }
+}
@@ -0,0 +1,122 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+//! # DMA interrupt line handler
+//!
+//! The DMA channels 0..=6 and 11..=14 have their own interrupt line. The channels 7 and 8 share the interrupt line
+//! ``DMA7_8`` and the channels 9 and 10 the interrupt line ``DMA9_10``. This handler branches to the specific handler
+//! implementation of the channel based on the DMA interrupt status register.
+//!
+
+use super::IsrChannel;
+use crate::{
+  sharedline::{SharedLine, SharedSource},
+  Interrupt,
+};
+use core::sync::atomic::AtomicU32;
+
+#[cfg(feature = "sim")]
+use crate::interface::dma_int_status;
+
+#[cfg(feature = "pi3")]
+#[cfg_attr(feature = "sim", allow(dead_code))]
+const PERIPHERAL_BASE: usize = 0x0_3F00_0000;
+#[cfg(feature = "pi4_low")]
+#[cfg_attr(feature = "sim", allow(dead_code))]
+const PERIPHERAL_BASE: usize = 0x0_FE00_0000;
+#[cfg(feature = "pi4_high")]
+#[cfg_attr(feature = "sim", allow(dead_code))]
+const PERIPHERAL_BASE: usize = 0x4_7E00_0000;
+
+/// The interrupt line of each DMA channel
+pub(crate) const DMA_LINES: [Interrupt; 15] = [
+  Interrupt::DMA0,
+  Interrupt::DMA1,
+  Interrupt::DMA2,
+  Interrupt::DMA3,
+  Interrupt::DMA4,
+  Interrupt::DMA5,
+  Interrupt::DMA6,
+  Interrupt::DMA7_8,
+  Interrupt::DMA7_8,
+  Interrupt::DMA9_10,
+  Interrupt::DMA9_10,
+  Interrupt::DMA11,
+  Interrupt::DMA12,
+  Interrupt::DMA13,
+  Interrupt::DMA14,
+];
+
+/// The DMA interrupt line shared between the channels 7 and 8
+pub(crate) static DMA7_8_LINE: SharedLine = SharedLine {
+  irq: Interrupt::DMA7_8,
+  status: dma7_8_status,
+  acknowledge: None,
+  disable: None,
+  line_handler: None,
+  sources: &DMA7_8_SOURCES,
+  active: AtomicU32::new(0),
+};
+
+static DMA7_8_SOURCES: [SharedSource; 2] = ruspiro_interrupt_macros::shared_sources!(DMA7_8);
+
+/// The DMA interrupt line shared between the channels 9 and 10
+pub(crate) static DMA9_10_LINE: SharedLine = SharedLine {
+  irq: Interrupt::DMA9_10,
+  status: dma9_10_status,
+  acknowledge: None,
+  disable: None,
+  line_handler: None,
+  sources: &DMA9_10_SOURCES,
+  active: AtomicU32::new(0),
+};
+
+static DMA9_10_SOURCES: [SharedSource; 2] = ruspiro_interrupt_macros::shared_sources!(DMA9_10);
+
+/// Get the interrupt line of the given DMA channel and it's shared line with the index of the channel within the
+/// sources of the line if the channel does not have it's own interrupt line
+///
+/// # Panics
+/// The function panics if the DMA channel is not in the range of 0..=14
+pub(crate) fn channel_line(channel: u8) -> (Interrupt, Option<(&'static SharedLine, usize)>) {
+  let irq = *DMA_LINES
+    .get(channel as usize)
+    .expect("DMA channel need to be in the range of 0..=14");
+  match channel {
+    7 | 8 => (irq, Some((&DMA7_8_LINE, channel as usize - 7))),
+    9 | 10 => (irq, Some((&DMA9_10_LINE, channel as usize - 9))),
+    _ => (irq, None),
+  }
+}
+
+#[allow(improper_ctypes_definitions)]
+pub(crate) extern "C" fn dma7_8_handler(channel: &IsrChannel) {
+  DMA7_8_LINE.dispatch(channel);
+}
+
+#[allow(improper_ctypes_definitions)]
+pub(crate) extern "C" fn dma9_10_handler(channel: &IsrChannel) {
+  DMA9_10_LINE.dispatch(channel);
+}
+
+/// The pending interrupts of the channels 7 and 8 in the order of their sources
+fn dma7_8_status() -> u32 {
+  (dma_int_status() >> 7) & 0b11
+}
+
+/// The pending interrupts of the channels 9 and 10 in the order of their sources
+fn dma9_10_status() -> u32 {
+  (dma_int_status() >> 9) & 0b11
+}
+
+/// Read the DMA interrupt status register indicating the channels having a pending interrupt
+#[cfg(not(feature = "sim"))]
+fn dma_int_status() -> u32 {
+  unsafe { core::ptr::read_volatile(DMA_INT_STATUS as *const u32) }
+}
+
+#[cfg(not(feature = "sim"))]
+const DMA_INT_STATUS: usize = PERIPHERAL_BASE + 0x0000_7FE0;
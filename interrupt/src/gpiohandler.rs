@@ -0,0 +1,235 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+//! # GPIO interrupt line handler
+//!
+//! The interrupt lines ``GpioBank0``, ``GpioBank1`` and ``GpioBank2`` are shared between the pins of the respective
+//! GPIO bank. Each bank is a shared line with the pins as it's sources. The line reads the event detect status
+//! registers, acknowledges the events of the pins that have been activated with [activate_gpio_pin] and branches to the
+//! handler implemented for the specific pin. Events of pins that have not been activated individually are left to the
+//! handler implemented for the whole bank.
+//!
+//! [activate_gpio_pin]: crate::activate_gpio_pin
+
+use super::IsrChannel;
+use crate::{
+  bitset::BitSet32,
+  sharedline::{SharedLine, SharedSource},
+  Interrupt,
+};
+use core::sync::atomic::AtomicU32;
+
+#[cfg(feature = "sim")]
+use crate::interface::{gpio_read, gpio_write};
+
+#[cfg(feature = "pi3")]
+#[cfg_attr(feature = "sim", allow(dead_code))]
+const PERIPHERAL_BASE: usize = 0x0_3F00_0000;
+#[cfg(feature = "pi4_low")]
+#[cfg_attr(feature = "sim", allow(dead_code))]
+const PERIPHERAL_BASE: usize = 0x0_FE00_0000;
+#[cfg(feature = "pi4_high")]
+#[cfg_attr(feature = "sim", allow(dead_code))]
+const PERIPHERAL_BASE: usize = 0x4_7E00_0000;
+
+/// The number of GPIO pins
+#[cfg(feature = "pi3")]
+pub(crate) const GPIO_PINS: u8 = 54;
+#[cfg(any(feature = "pi4_low", feature = "pi4_high"))]
+pub(crate) const GPIO_PINS: u8 = 58;
+
+/// The pins of the GPIO banks 0..2 that share the interrupt line ``GpioBank0``, ``GpioBank1`` and ``GpioBank2``
+pub(crate) const GPIO_BANK_PINS: [u64; 3] = [
+  0x0000_0000_0FFF_FFFF, // pins 0..=27
+  0x0000_3FFF_F000_0000, // pins 28..=45
+  0x03FF_C000_0000_0000, // pins 46..=57
+];
+
+/// The offsets of the first register of each register pair within the GPIO block. The second register of the pair
+/// covers the pins 32..=57
+pub(crate) const GPEDS0: usize = 0x40;
+pub(crate) const GPREN0: usize = 0x4C;
+pub(crate) const GPFEN0: usize = 0x58;
+pub(crate) const GPHEN0: usize = 0x64;
+pub(crate) const GPLEN0: usize = 0x70;
+pub(crate) const GPAREN0: usize = 0x7C;
+pub(crate) const GPAFEN0: usize = 0x88;
+
+/// The registers enabling the event detection of a pin
+const DETECT_ENABLE: [usize; 6] = [GPREN0, GPFEN0, GPHEN0, GPLEN0, GPAREN0, GPAFEN0];
+
+/// The event detected on a GPIO pin that raises it's interrupt
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum GpioEdge {
+  /// A rising edge sampled with the system clock
+  Rising,
+  /// A falling edge sampled with the system clock
+  Falling,
+  /// A rising or a falling edge sampled with the system clock
+  Both,
+  /// The pin is high
+  High,
+  /// The pin is low
+  Low,
+  /// A rising edge not sampled with the system clock, so even very short pulses are detected
+  AsyncRising,
+  /// A falling edge not sampled with the system clock, so even very short pulses are detected
+  AsyncFalling,
+  /// A rising or a falling edge not sampled with the system clock
+  AsyncBoth,
+}
+
+impl GpioEdge {
+  /// The registers need to be enabled for the pin to detect this event
+  pub(crate) fn registers(self) -> &'static [usize] {
+    match self {
+      GpioEdge::Rising => &[GPREN0],
+      GpioEdge::Falling => &[GPFEN0],
+      GpioEdge::Both => &[GPREN0, GPFEN0],
+      GpioEdge::High => &[GPHEN0],
+      GpioEdge::Low => &[GPLEN0],
+      GpioEdge::AsyncRising => &[GPAREN0],
+      GpioEdge::AsyncFalling => &[GPAFEN0],
+      GpioEdge::AsyncBoth => &[GPAREN0, GPAFEN0],
+    }
+  }
+}
+
+/// The interrupt line of the GPIO bank 0 shared between the pins 0..=27
+pub(crate) static GPIO_BANK0_LINE: SharedLine = SharedLine {
+  irq: Interrupt::GpioBank0,
+  status: bank_events::<0>,
+  acknowledge: Some(acknowledge_bank::<0>),
+  disable: Some(disable_bank::<0>),
+  line_handler: Some(crate::__irq_handler__GpioBank0),
+  sources: &GPIO_BANK0_PINS,
+  active: AtomicU32::new(0),
+};
+
+/// The interrupt line of the GPIO bank 1 shared between the pins 28..=45
+pub(crate) static GPIO_BANK1_LINE: SharedLine = SharedLine {
+  irq: Interrupt::GpioBank1,
+  status: bank_events::<1>,
+  acknowledge: Some(acknowledge_bank::<1>),
+  disable: Some(disable_bank::<1>),
+  line_handler: Some(crate::__irq_handler__GpioBank1),
+  sources: &GPIO_BANK1_PINS,
+  active: AtomicU32::new(0),
+};
+
+/// The interrupt line of the GPIO bank 2 shared between the pins 46..=57
+pub(crate) static GPIO_BANK2_LINE: SharedLine = SharedLine {
+  irq: Interrupt::GpioBank2,
+  status: bank_events::<2>,
+  acknowledge: Some(acknowledge_bank::<2>),
+  disable: Some(disable_bank::<2>),
+  line_handler: Some(crate::__irq_handler__GpioBank2),
+  sources: &GPIO_BANK2_PINS,
+  active: AtomicU32::new(0),
+};
+
+static GPIO_BANK0_PINS: [SharedSource; 28] = ruspiro_interrupt_macros::shared_sources!(GpioBank0);
+static GPIO_BANK1_PINS: [SharedSource; 18] = ruspiro_interrupt_macros::shared_sources!(GpioBank1);
+static GPIO_BANK2_PINS: [SharedSource; 12] = ruspiro_interrupt_macros::shared_sources!(GpioBank2);
+
+/// Get the shared line of the bank the given pin is raising it's events on and the index of the pin within the sources
+/// of the line
+///
+/// # Panics
+/// The function panics if the pin does not exist
+pub(crate) fn pin_line(pin: u8) -> (&'static SharedLine, usize) {
+  assert!(
+    pin < GPIO_PINS,
+    "GPIO pin need to be in the range of 0..{}",
+    GPIO_PINS
+  );
+  match pin {
+    0..=27 => (&GPIO_BANK0_LINE, pin as usize),
+    28..=45 => (&GPIO_BANK1_LINE, pin as usize - 28),
+    _ => (&GPIO_BANK2_LINE, pin as usize - 46),
+  }
+}
+
+/// Enable the detection of the given event on the pin. Events detected before are acknowledged.
+pub(crate) fn enable_pin(pin: u8, edge: GpioEdge) {
+  configure_pin(pin, edge.registers());
+  acknowledge(1 << pin);
+}
+
+#[allow(improper_ctypes_definitions)]
+pub(crate) extern "C" fn gpio_bank0_handler(channel: &IsrChannel) {
+  GPIO_BANK0_LINE.dispatch(channel);
+}
+
+#[allow(improper_ctypes_definitions)]
+pub(crate) extern "C" fn gpio_bank1_handler(channel: &IsrChannel) {
+  GPIO_BANK1_LINE.dispatch(channel);
+}
+
+#[allow(improper_ctypes_definitions)]
+pub(crate) extern "C" fn gpio_bank2_handler(channel: &IsrChannel) {
+  GPIO_BANK2_LINE.dispatch(channel);
+}
+
+/// Read the events detected on the pins of the given bank. The event of each pin is indicated by the bit at it's
+/// position within the bank.
+fn bank_events<const BANK: usize>() -> u32 {
+  let events = gpio_read(GPEDS0) as u64 | (gpio_read(GPEDS0 + 4) as u64) << 32;
+  ((events & GPIO_BANK_PINS[BANK]) >> GPIO_BANK_PINS[BANK].trailing_zeros()) as u32
+}
+
+/// Acknowledge the events of the given pins of the bank
+fn acknowledge_bank<const BANK: usize>(pins: u32) {
+  acknowledge((pins as u64) << GPIO_BANK_PINS[BANK].trailing_zeros());
+}
+
+/// Disable the event detection of the given pins of the bank and acknowledge the events detected before
+fn disable_bank<const BANK: usize>(pins: u32) {
+  let first = GPIO_BANK_PINS[BANK].trailing_zeros();
+  for pin in BitSet32(pins).iter() {
+    configure_pin((first + pin) as u8, &[]);
+  }
+  acknowledge((pins as u64) << first);
+}
+
+/// Enable the given event detect registers for the pin and disable all others
+fn configure_pin(pin: u8, registers: &[usize]) {
+  let offset = (pin as usize >> 5) << 2;
+  let bit = 1 << (pin & 0x1F);
+  for &register in DETECT_ENABLE.iter() {
+    let value = gpio_read(register + offset) & !bit;
+    if registers.contains(&register) {
+      gpio_write(register + offset, value | bit);
+    } else {
+      gpio_write(register + offset, value);
+    }
+  }
+}
+
+/// Acknowledge the events of the given pins. The event detect status bits are cleared by writing 1 to them.
+fn acknowledge(pins: u64) {
+  if pins as u32 != 0 {
+    gpio_write(GPEDS0, pins as u32);
+  }
+  if (pins >> 32) as u32 != 0 {
+    gpio_write(GPEDS0 + 4, (pins >> 32) as u32);
+  }
+}
+
+/// Read the GPIO register at the given offset
+#[cfg(not(feature = "sim"))]
+fn gpio_read(offset: usize) -> u32 {
+  unsafe { core::ptr::read_volatile((GPIO_BASE + offset) as *const u32) }
+}
+
+/// Write the GPIO register at the given offset
+#[cfg(not(feature = "sim"))]
+fn gpio_write(offset: usize, value: u32) {
+  unsafe { core::ptr::write_volatile((GPIO_BASE + offset) as *mut u32, value) };
+}
+
+#[cfg(not(feature = "sim"))]
+const GPIO_BASE: usize = PERIPHERAL_BASE + 0x0020_0000;
@@ -0,0 +1,343 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # GIC-400 interrupt controller
+//!
+//! The BCM2711 (Raspberry Pi 4) contains a GIC-400 (GICv2) interrupt controller that is enabled by the firmware by
+//! default. The GIC consists of the *distributor* that is shared between all cores and a *CPU interface* that exists
+//! for each core. Interrupts are acknowledged by reading the interrupt acknowledge register (IAR) of the CPU interface
+//! and completed by writing the acknowledged value to the end of interrupt register (EOIR).
+//!
+//! The GIC uses its own interrupt ID's. They are mapped to the banks and bit positions of the [Interrupt] enum to keep
+//! the handler dispatching the same as for the legacy interrupt controller.
+//!
+//! GIC interrupt ID | Source
+//! -----------------|---------------------------------------------------------
+//! 26, 27, 29, 30   | core timer (CNTHP, CNTV, CNTPS, CNTPNS) - private per core
+//! 32 .. 47         | ARM local mailbox 0..3 of core 0..3
+//! 53               | ARM local timer
+//! 64 .. 71         | ARMC (ARM timer, mailbox, doorbells, VPU halted, errors)
+//! 96 .. 159        | VideoCore peripheral interrupts 0 .. 63
+//!
+
+use core::ptr::write_volatile;
+use core::sync::atomic::{AtomicU32, Ordering};
+use ruspiro_mmio_register::define_mmio_register;
+
+use super::*;
+use crate::{Interrupt, RoutingMode};
+
+#[cfg(feature = "pi3")]
+compile_error!("The feature \"pi4_gic\" is only available for the Raspberry Pi4");
+
+/// The GIC-400 is located in the ARM local peripheral address space
+const GIC_BASE: usize = ARM_CORE_BASE + 0x4_0000;
+/// Base address of the distributor
+const GICD_BASE: usize = GIC_BASE + 0x1000;
+/// Base address of the CPU interface
+const GICC_BASE: usize = GIC_BASE + 0x2000;
+
+/// Offsets of the distributor registers that exist as a list of consecutive registers
+const GICD_ISENABLER: usize = 0x100;
+const GICD_ICENABLER: usize = 0x180;
+const GICD_ICPENDR: usize = 0x280;
+const GICD_ICACTIVER: usize = 0x380;
+const GICD_IPRIORITYR: usize = 0x400;
+const GICD_ITARGETSR: usize = 0x800;
+const GICD_ICFGR: usize = 0xC00;
+
+/// Number of interrupt ID's wired to the GIC-400 of the BCM2711
+const GIC_NUM_IRQS: usize = 256;
+/// Interrupt ID's 1020 and above indicate that there is no interrupt to be handled
+const GIC_SPURIOUS_ID: u32 = 1020;
+/// Mask of the interrupt ID within the IAR/EOIR value
+const GIC_ID_MASK: u32 = 0x3FF;
+
+/// Default priority of all interrupts
+const GIC_DEFAULT_PRIORITY: u32 = 0xA0A0_A0A0;
+/// Interrupts with a priority value lower than this are signaled to the core
+const GIC_PRIORITY_MASK: u32 = 0xF0;
+
+/// Private peripheral interrupt ID's of the generic timer
+const GIC_PPI_CNTHP: u32 = 26;
+const GIC_PPI_CNTV: u32 = 27;
+const GIC_PPI_CNTPS: u32 = 29;
+const GIC_PPI_CNTPNS: u32 = 30;
+/// The ARM local mailbox interrupts. Each core has 4 mailboxes with consecutive ID's
+const GIC_LOCAL_MAILBOX_BASE: u32 = 32;
+const GIC_LOCAL_MAILBOX_LAST: u32 = GIC_LOCAL_MAILBOX_BASE + 15;
+/// The ARM local timer interrupt
+const GIC_LOCAL_TIMER: u32 = 53;
+/// The ARMC interrupts (ARM timer, mailbox, doorbells...)
+const GIC_ARMC_BASE: u32 = 64;
+const GIC_ARMC_LAST: u32 = GIC_ARMC_BASE + 7;
+/// The VideoCore peripheral interrupts 0 .. 63
+const GIC_VC_BASE: u32 = 96;
+const GIC_VC_LAST: u32 = GIC_VC_BASE + 63;
+
+/// The value read from the IAR that need to be written to the EOIR once the interrupt has been handled. As interrupts
+/// are acknowledged individually by each core there is one entry per core.
+static ACTIVE_IRQ: [AtomicU32; 4] = [
+  AtomicU32::new(GIC_SPURIOUS_ID),
+  AtomicU32::new(GIC_SPURIOUS_ID),
+  AtomicU32::new(GIC_SPURIOUS_ID),
+  AtomicU32::new(GIC_SPURIOUS_ID),
+];
+
+pub(crate) fn initialize() {
+  // disable the distributor while it is configured
+  GICD_CTLR::Register.write_value(GICD_CTLR::ENABLE::DISABLED);
+
+  // disable all shared peripheral interrupts, clear their pending and active state, apply the default priority,
+  // route them to core 0 and configure them to be level sensitive
+  for id in (32..GIC_NUM_IRQS).step_by(32) {
+    gicd_write(GICD_ICENABLER + id / 8, 0xFFFF_FFFF);
+    gicd_write(GICD_ICPENDR + id / 8, 0xFFFF_FFFF);
+    gicd_write(GICD_ICACTIVER + id / 8, 0xFFFF_FFFF);
+  }
+  for id in (32..GIC_NUM_IRQS).step_by(4) {
+    gicd_write(GICD_IPRIORITYR + id, GIC_DEFAULT_PRIORITY);
+    gicd_write(GICD_ITARGETSR + id, 0x0101_0101);
+  }
+  // the 4 mailbox interrupts of each core are targeted at the core they belong to
+  for core in 0..4 {
+    let id = GIC_LOCAL_MAILBOX_BASE as usize + (core << 2);
+    gicd_write(GICD_ITARGETSR + id, 0x0101_0101 << core);
+  }
+  for id in (32..GIC_NUM_IRQS).step_by(16) {
+    gicd_write(GICD_ICFGR + id / 4, 0);
+  }
+
+  GICD_CTLR::Register.write_value(GICD_CTLR::ENABLE::ENABLED);
+
+  // the CPU interface of the core running the initialization is configured right away
+  initialize_core();
+
+  // setup IPI (inter-processor-interrupts)
+  // raising IRQ only if something is written to mailbox 3 for any of the cores
+  CORE0_MB_INT_CNTRL::Register.set(1 << 3);
+  CORE1_MB_INT_CNTRL::Register.set(1 << 3);
+  CORE2_MB_INT_CNTRL::Register.set(1 << 3);
+  CORE3_MB_INT_CNTRL::Register.set(1 << 3);
+}
+
+/// Initialize the GIC CPU interface and the banked private interrupts of the current core.
+pub(crate) fn initialize_core() {
+  // the first register of each list is banked for each core and covers the SGI's and PPI's
+  gicd_write(GICD_ICENABLER, 0xFFFF_FFFF);
+  gicd_write(GICD_ICPENDR, 0xFFFF_FFFF);
+  gicd_write(GICD_ICACTIVER, 0xFFFF_FFFF);
+  for id in (0..32).step_by(4) {
+    gicd_write(GICD_IPRIORITYR + id, GIC_DEFAULT_PRIORITY);
+  }
+
+  GICC_PMR::Register.set(GIC_PRIORITY_MASK);
+  GICC_CTLR::Register.write_value(GICC_CTLR::ENABLE::ENABLED);
+
+  #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
+  unsafe {
+    asm!("dmb sy")
+  };
+}
+
+/// Activate the interrupt in the distributor. The core timer interrupts are private to each core. Those are activated
+/// for the core calling this function only.
+pub(crate) fn activate(irq: Interrupt) {
+  // the ARM local sources need to be enabled at the ARM local peripheral as well
+  match irq {
+    Interrupt::LocalTimer => {
+      LOCAL_TIMER_CTRL::Register.modify_value(LOCAL_TIMER_CTRL::IRQ_ENABLE::ENABLED);
+    }
+    _ => {
+      if let Some((core, mailbox)) = irq.mailbox() {
+        modify_mailbox_int(core, mailbox, true);
+      }
+    }
+  }
+
+  if let Some(id) = gic_id(irq) {
+    gicd_write(GICD_ISENABLER + (id as usize >> 5) * 4, 1 << (id & 0x1F));
+  }
+}
+
+pub(crate) fn deactivate(irq: Interrupt) {
+  if let Some(id) = gic_id(irq) {
+    gicd_write(GICD_ICENABLER + (id as usize >> 5) * 4, 1 << (id & 0x1F));
+  }
+
+  match irq {
+    Interrupt::LocalTimer => {
+      LOCAL_TIMER_CTRL::Register.modify_value(LOCAL_TIMER_CTRL::IRQ_ENABLE::DISABLED);
+    }
+    _ => {
+      if let Some((core, mailbox)) = irq.mailbox() {
+        modify_mailbox_int(core, mailbox, false);
+      }
+    }
+  }
+}
+
+/// Activate a core private interrupt for the given core. The enable register of the private interrupts is banked for
+/// each core and can only be accessed by the core itself. So the interrupt is only activated if the given core is the
+/// one calling this function.
+pub(crate) fn activate_on_core(irq: Interrupt, core: usize) {
+  if core == core_id() {
+    activate(irq);
+  }
+}
+
+/// Deactivate a core private interrupt for the given core. Like the activation this only takes effect if the given core
+/// is the one calling this function.
+pub(crate) fn deactivate_on_core(irq: Interrupt, core: usize) {
+  if core == core_id() {
+    deactivate(irq);
+  }
+}
+
+/// Route the shared peripheral interrupts of the ARMC and the VideoCore to the given core. The GIC signals those
+/// interrupts as FIQ only if they are configured as secure (group 0) interrupts which is not possible from the
+/// non-secure world the crate is running in.
+///
+/// # Panics
+/// Panics if the interrupts shall be routed as FIQ
+pub(crate) fn route_gpu_irqs(core: usize, mode: RoutingMode) {
+  assert!(mode == RoutingMode::Irq, "GIC interrupts can only be routed as IRQ");
+  let targets = 0x0101_0101 << core;
+  for id in (GIC_ARMC_BASE as usize..=GIC_VC_LAST as usize).step_by(4) {
+    gicd_write(GICD_ITARGETSR + id, targets);
+  }
+}
+
+/// Route the local timer interrupt to the given core. Like the shared peripheral interrupts it can only be signaled as
+/// IRQ.
+///
+/// # Panics
+/// Panics if the interrupt shall be routed as FIQ
+pub(crate) fn route_local_timer(core: usize, mode: RoutingMode) {
+  assert!(mode == RoutingMode::Irq, "GIC interrupts can only be routed as IRQ");
+  // the target register contains one byte for each interrupt ID
+  let target = (GICD_BASE + GICD_ITARGETSR + GIC_LOCAL_TIMER as usize) as *mut u8;
+  unsafe { write_volatile(target, 1 << core) };
+}
+
+/// Acknowledge the highest priority pending interrupt of the current core and return it as the bit set in the
+/// corresponding bank. The acknowledged interrupt remains active until [end_of_interrupt] is called.
+pub fn get_pending_irqs() -> [u32; 4] {
+  let mut pendings = [0; 4];
+  let iar = GICC_IAR::Register.get();
+  let id = iar & GIC_ID_MASK;
+  if id >= GIC_SPURIOUS_ID {
+    return pendings;
+  }
+
+  match irq_slot(id) {
+    Some((bank, bit)) => {
+      pendings[bank] = 1 << bit;
+      ACTIVE_IRQ[core_id()].store(iar, Ordering::Release);
+    }
+    // there is no handler possible for this interrupt, so complete it right away
+    None => GICC_EOIR::Register.set(iar),
+  }
+
+  pendings
+}
+
+/// Signal the end of the interrupt acknowledged with [get_pending_irqs] to the GIC
+pub(crate) fn end_of_interrupt() {
+  let iar = ACTIVE_IRQ[core_id()].swap(GIC_SPURIOUS_ID, Ordering::AcqRel);
+  if iar & GIC_ID_MASK < GIC_SPURIOUS_ID {
+    GICC_EOIR::Register.set(iar);
+  }
+}
+
+/// The GIC signals interrupts as FIQ only if they are configured as secure (group 0) interrupts which is not
+/// possible from the non-secure world the crate is running in. So there is nothing to activate.
+pub(crate) fn activate_fiq(_irq: Interrupt) {}
+
+/// There is nothing to deactivate as no interrupt is raised as FIQ with the GIC
+pub(crate) fn deactivate_fiq(_irq: Interrupt) {}
+
+/// There are no interrupts raised as FIQ with the GIC
+pub fn get_pending_fiqs() -> [u32; 4] {
+  [0; 4]
+}
+
+/// Get the GIC interrupt ID of the given [Interrupt]
+fn gic_id(irq: Interrupt) -> Option<u32> {
+  let num = irq as u32;
+  match irq {
+    Interrupt::CntPsIrq => Some(GIC_PPI_CNTPS),
+    Interrupt::CntPnsIrq => Some(GIC_PPI_CNTPNS),
+    Interrupt::CntHpIrq => Some(GIC_PPI_CNTHP),
+    Interrupt::CntVIrq => Some(GIC_PPI_CNTV),
+    Interrupt::LocalTimer => Some(GIC_LOCAL_TIMER),
+    // the pending cascade and the GPU core interrupt only exist for the legacy interrupt controller
+    Interrupt::ArmPending1 | Interrupt::ArmPending2 | Interrupt::CoreGPU => None,
+    _ => match num >> 5 {
+      0 | 1 => Some(GIC_VC_BASE + num),
+      2 => Some(GIC_ARMC_BASE + (num & 0x1F)),
+      // each core has 4 mailboxes with consecutive ID's
+      _ => irq
+        .mailbox()
+        .map(|(core, mailbox)| GIC_LOCAL_MAILBOX_BASE + (core << 2) as u32 + mailbox as u32),
+    },
+  }
+}
+
+/// Get the bank and bit position of the [Interrupt] that corresponds to the given GIC interrupt ID
+fn irq_slot(id: u32) -> Option<(usize, u32)> {
+  let slot = |irq: Interrupt| Some(((irq as u32 >> 5) as usize, irq as u32 & 0x1F));
+  match id {
+    GIC_PPI_CNTPS => slot(Interrupt::CntPsIrq),
+    GIC_PPI_CNTPNS => slot(Interrupt::CntPnsIrq),
+    GIC_PPI_CNTHP => slot(Interrupt::CntHpIrq),
+    GIC_PPI_CNTV => slot(Interrupt::CntVIrq),
+    GIC_LOCAL_MAILBOX_BASE..=GIC_LOCAL_MAILBOX_LAST => {
+      let mailbox = (id - GIC_LOCAL_MAILBOX_BASE) as usize;
+      slot(Interrupt::core_mailbox(mailbox >> 2, mailbox & 0x3))
+    }
+    GIC_LOCAL_TIMER => slot(Interrupt::LocalTimer),
+    GIC_ARMC_BASE..=GIC_ARMC_LAST => Some((2, id - GIC_ARMC_BASE)),
+    GIC_VC_BASE..=GIC_VC_LAST => {
+      let num = id - GIC_VC_BASE;
+      Some(((num >> 5) as usize, num & 0x1F))
+    }
+    _ => None,
+  }
+}
+
+/// Write to a distributor register given by it's offset
+fn gicd_write(offset: usize, value: u32) {
+  unsafe { write_volatile((GICD_BASE + offset) as *mut u32, value) };
+}
+
+// Define the GIC-400 registers that are accessed as single registers
+define_mmio_register![
+  /// Distributor control register
+  GICD_CTLR<ReadWrite<u32>@(GICD_BASE + 0x000)> {
+    ENABLE OFFSET(0) [
+      ENABLED = 1,
+      DISABLED = 0
+    ]
+  },
+  /// CPU interface control register
+  GICC_CTLR<ReadWrite<u32>@(GICC_BASE + 0x000)> {
+    ENABLE OFFSET(0) [
+      ENABLED = 1,
+      DISABLED = 0
+    ]
+  },
+  /// CPU interface priority mask register
+  GICC_PMR<ReadWrite<u32>@(GICC_BASE + 0x004)>,
+  /// CPU interface interrupt acknowledge register
+  GICC_IAR<ReadOnly<u32>@(GICC_BASE + 0x00C)> {
+    INTERRUPT_ID OFFSET(0) BITS(10),
+    CPU_ID OFFSET(10) BITS(3)
+  },
+  /// CPU interface end of interrupt register
+  GICC_EOIR<WriteOnly<u32>@(GICC_BASE + 0x010)>
+];
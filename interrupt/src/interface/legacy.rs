@@ -0,0 +1,527 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # Legacy interrupt controller
+//!
+//! The interrupt controller of the BCM2837 (Raspberry Pi 3) that is also available on the BCM2711 (Raspberry Pi 4) if
+//! this one is not running with the GIC-400 enabled. The register layout differs between both variants, so the
+//! pending, enable and disable registers are mapped to the banks of the [Interrupt] enum based on the active feature.
+//!
+
+#[cfg(any(feature = "pi4_low", feature = "pi4_high"))]
+use core::{
+  ptr::{read_volatile, write_volatile},
+  sync::atomic::{AtomicUsize, Ordering},
+};
+#[cfg(feature = "pi3")]
+use ruspiro_mmio_register::define_mmio_register;
+
+use super::*;
+use crate::{Interrupt, RoutingMode};
+
+pub(crate) fn initialize() {
+  // disable all interrupts in all 3 banks by default
+  #[cfg(feature = "pi3")]
+  {
+    IRQ0_DISABLE_1::Register.set(0xFFFF_FFFF);
+    IRQ0_DISABLE_2::Register.set(0xFFFF_FFFF);
+    IRQ0_DISABLE_B::Register.set(0xFFFF_FFFF);
+  }
+
+  // the Raspberry Pi4 provides the enable and disable registers for each core and for IRQ and FIQ
+  #[cfg(any(feature = "pi4_low", feature = "pi4_high"))]
+  {
+    for core in 0..4 {
+      for bank in 0..3 {
+        armc_write(ARMC_CLR_EN, core, RoutingMode::Irq, bank, 0xFFFF_FFFF);
+        armc_write(ARMC_CLR_EN, core, RoutingMode::Fiq, bank, 0xFFFF_FFFF);
+      }
+    }
+    for routing in GPU_ROUTING.iter() {
+      routing.store(0, Ordering::Release);
+    }
+  }
+
+  #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
+  unsafe {
+    asm!("dmb sy")
+  };
+
+  // set the routing of GPU interrupts to core 0
+  // the register exists on PI4 as well but only configures routing for AXI_ERR_IRQ (ARM cache L2 error interrupt)
+  #[cfg(feature = "pi3")]
+  GPU_INT_ROUTING::Register.set(0);
+
+  // setup IPI (inter-processor-interrupts)
+  // raising IRQ only if something is written to mailbox 3 for any of the cores
+  CORE0_MB_INT_CNTRL::Register.set(1 << 3);
+  CORE1_MB_INT_CNTRL::Register.set(1 << 3);
+  CORE2_MB_INT_CNTRL::Register.set(1 << 3);
+  CORE3_MB_INT_CNTRL::Register.set(1 << 3);
+}
+
+/// The legacy interrupt controller does not require any core specific initialization
+pub(crate) fn initialize_core() {}
+
+pub(crate) fn activate(irq: Interrupt) {
+  let bank = (irq as u32) >> 5;
+  let enable_bit = 1 << ((irq as u32) & 0x1F);
+  match bank {
+    #[cfg(feature = "pi3")]
+    0 => IRQ0_ENABLE_1::Register.set(enable_bit),
+    #[cfg(feature = "pi3")]
+    1 => IRQ0_ENABLE_2::Register.set(enable_bit),
+    #[cfg(feature = "pi3")]
+    2 => IRQ0_ENABLE_B::Register.set(enable_bit),
+    // the GPU interrupts are enabled for the core the GPU IRQ's are routed to
+    #[cfg(any(feature = "pi4_low", feature = "pi4_high"))]
+    0..=2 => {
+      let (core, mode) = (gpu_routing(RoutingMode::Irq), RoutingMode::Irq);
+      armc_write(ARMC_SET_EN, core, mode, bank as usize, enable_bit);
+    }
+    3 => {
+      // this bank is special as it covers the Core specific interrupts that are
+      // configured for their specific device
+      match irq {
+        // the core timer interrupts are activated for core 0 if not requested for a specific core
+        Interrupt::CntPsIrq | Interrupt::CntPnsIrq | Interrupt::CntHpIrq | Interrupt::CntVIrq => {
+          activate_on_core(irq, 0);
+        }
+        Interrupt::CoreGPU => (), // seems GPU interrupt cant be enabled/disabled as they are triggered from GPU
+        //Interrupt::CorePMU => (),
+        //Interrupt::CoreAxi => (),
+        Interrupt::LocalTimer => {
+          LOCAL_TIMER_CTRL::Register.modify_value(LOCAL_TIMER_CTRL::IRQ_ENABLE::ENABLED);
+        }
+        // the mailbox interrupts are enabled in the mailbox interrupt control register of their core
+        _ => {
+          if let Some((core, mailbox)) = irq.mailbox() {
+            modify_mailbox_int(core, mailbox, true);
+          }
+        }
+      };
+    }
+    _ => (),
+  }
+}
+
+pub(crate) fn deactivate(irq: Interrupt) {
+  let bank = (irq as u32) >> 5;
+  let disable_bit = 1 << ((irq as u32) & 0x1F);
+  match bank {
+    #[cfg(feature = "pi3")]
+    0 => IRQ0_DISABLE_1::Register.set(disable_bit),
+    #[cfg(feature = "pi3")]
+    1 => IRQ0_DISABLE_2::Register.set(disable_bit),
+    #[cfg(feature = "pi3")]
+    2 => IRQ0_DISABLE_B::Register.set(disable_bit),
+    // the GPU interrupts are disabled for the core the GPU IRQ's are routed to
+    #[cfg(any(feature = "pi4_low", feature = "pi4_high"))]
+    0..=2 => {
+      let (core, mode) = (gpu_routing(RoutingMode::Irq), RoutingMode::Irq);
+      armc_write(ARMC_CLR_EN, core, mode, bank as usize, disable_bit);
+    }
+    3 => {
+      // this bank is special as it covers the Core specific interrupts that are
+      // configured for their specific device
+      match irq {
+        // the core timer interrupts are activated for core 0 if not requested for a specific core
+        Interrupt::CntPsIrq | Interrupt::CntPnsIrq | Interrupt::CntHpIrq | Interrupt::CntVIrq => {
+          deactivate_on_core(irq, 0);
+        }
+        Interrupt::CoreGPU => (), // seems GPU interrupt cant be enabled/disabled as they are triggered from GPU
+        //Interrupt::CorePMU => (),
+        //Interrupt::CoreAxi => (),
+        Interrupt::LocalTimer => {
+          LOCAL_TIMER_CTRL::Register.modify_value(LOCAL_TIMER_CTRL::IRQ_ENABLE::DISABLED);
+        }
+        // the mailbox interrupts are disabled in the mailbox interrupt control register of their core
+        _ => {
+          if let Some((core, mailbox)) = irq.mailbox() {
+            modify_mailbox_int(core, mailbox, false);
+          }
+        }
+      };
+    }
+    _ => (),
+  }
+}
+
+/// Activate a core specific interrupt for the given core. Only the core timer interrupts could be activated for each
+/// core individually.
+pub(crate) fn activate_on_core(irq: Interrupt, core: usize) {
+  if let Some(bit) = core_timer_bit(irq) {
+    modify_core_timer_irq(core, bit, true);
+  }
+}
+
+/// Deactivate a core specific interrupt for the given core.
+pub(crate) fn deactivate_on_core(irq: Interrupt, core: usize) {
+  if let Some(bit) = core_timer_bit(irq) {
+    modify_core_timer_irq(core, bit, false);
+  }
+}
+
+/// Get the bit of the core timer interrupt control register that enables the given interrupt. The core timer interrupts
+/// are placed in the core specific bank at the same bit positions.
+fn core_timer_bit(irq: Interrupt) -> Option<u32> {
+  match irq {
+    Interrupt::CntPsIrq | Interrupt::CntPnsIrq | Interrupt::CntHpIrq | Interrupt::CntVIrq => Some(irq as u32 & 0x1F),
+    _ => None,
+  }
+}
+
+/// Set or clear the given bit in the core timer interrupt control register of the given core
+fn modify_core_timer_irq(core: usize, bit: u32, set: bool) {
+  let update = |value: u32| {
+    if set {
+      value | (1 << bit)
+    } else {
+      value & !(1 << bit)
+    }
+  };
+  match core {
+    0 => CORE0_TIMER_IRQ::Register.set(update(CORE0_TIMER_IRQ::Register.get())),
+    1 => CORE1_TIMER_IRQ::Register.set(update(CORE1_TIMER_IRQ::Register.get())),
+    2 => CORE2_TIMER_IRQ::Register.set(update(CORE2_TIMER_IRQ::Register.get())),
+    _ => CORE3_TIMER_IRQ::Register.set(update(CORE3_TIMER_IRQ::Register.get())),
+  }
+}
+
+pub fn get_pending_irqs() -> [u32; 4] {
+  // get the core the interrupt has been generated
+  let core = core_id();
+  let core_pending = core_irq_pending(core);
+  // use the core specific registers to retrieve the pending interrupts
+  // NOTE: the order of the register is different between PI3 and PI4 to
+  // enable a stable list of pending interrupts for the caller
+  #[cfg(any(feature = "pi4_low", feature = "pi4_high"))]
+  {
+    let mut pendings = [0, 0, 0, core_pending];
+    for (bank, pending) in pendings.iter_mut().take(3).enumerate() {
+      *pending = armc_read(ARMC_PENDING, core, RoutingMode::Irq, bank)
+        & armc_read(ARMC_SET_EN, core, RoutingMode::Irq, bank);
+    }
+    pendings
+  }
+  #[cfg(feature = "pi3")]
+  {
+    // the GPU pending registers are shared between all cores. Only the core the GPU interrupts are routed to
+    // will see them signaled in it's core specific pending register
+    if core_pending & CORE_GPU_PENDING == 0 {
+      return [0, 0, 0, core_pending];
+    }
+    [
+      IRQ0_PENDING_1::Register.get() & IRQ0_ENABLE_1::Register.get(),
+      IRQ0_PENDING_2::Register.get() & IRQ0_ENABLE_2::Register.get(),
+      IRQ0_PENDING_B::Register.get() & IRQ0_ENABLE_B::Register.get(),
+      core_pending,
+    ]
+  }
+}
+
+/// Route the GPU IRQ's or FIQ's to the given core. On the Raspberry Pi3 this is configured with the GPU interrupt
+/// routing register. The Raspberry Pi4 provides the enable registers for each core and exception kind. So the
+/// interrupts currently enabled for the given exception kind are moved to the enable registers of the new core.
+pub(crate) fn route_gpu_irqs(core: usize, mode: RoutingMode) {
+  #[cfg(feature = "pi3")]
+  {
+    let routing = GPU_INT_ROUTING::Register.get();
+    match mode {
+      RoutingMode::Irq => GPU_INT_ROUTING::Register.set((routing & !0b0011) | core as u32),
+      RoutingMode::Fiq => GPU_INT_ROUTING::Register.set((routing & !0b1100) | (core as u32) << 2),
+    }
+  }
+
+  #[cfg(any(feature = "pi4_low", feature = "pi4_high"))]
+  {
+    let current_core = gpu_routing(mode);
+    for bank in 0..3 {
+      let enabled = armc_read(ARMC_SET_EN, current_core, mode, bank);
+      armc_write(ARMC_CLR_EN, current_core, mode, bank, enabled);
+      armc_write(ARMC_SET_EN, core, mode, bank, enabled);
+    }
+    GPU_ROUTING[mode as usize].store(core, Ordering::Release);
+  }
+}
+
+/// Route the local timer interrupt to the IRQ or FIQ of the given core
+pub(crate) fn route_local_timer(core: usize, mode: RoutingMode) {
+  let fiq = if mode == RoutingMode::Fiq { 0b100 } else { 0 };
+  LOCAL_TIMER_ROUTING::Register.set(core as u32 | fiq);
+}
+
+/// The GPU interrupt bit within the core specific pending register
+#[cfg(feature = "pi3")]
+const CORE_GPU_PENDING: u32 = 1 << 8;
+
+/// Read the core specific pending interrupts of the given core.
+fn core_irq_pending(core: usize) -> u32 {
+  let pending = match core {
+    0 => CORE0_IRQ_PENDING::Register.get(),
+    1 => CORE1_IRQ_PENDING::Register.get(),
+    2 => CORE2_IRQ_PENDING::Register.get(),
+    _ => CORE3_IRQ_PENDING::Register.get(),
+  };
+  map_core_pending(core, pending)
+}
+
+/// Read the core specific pending fast interrupts of the given core.
+fn core_fiq_pending(core: usize) -> u32 {
+  let pending = match core {
+    0 => CORE0_FIQ_PENDING::Register.get(),
+    1 => CORE1_FIQ_PENDING::Register.get(),
+    2 => CORE2_FIQ_PENDING::Register.get(),
+    _ => CORE3_FIQ_PENDING::Register.get(),
+  };
+  map_core_pending(core, pending)
+}
+
+/// The mailbox interrupts are reported at the bits 4..7 for the mailboxes 0..3 of the core. As the bank of the core
+/// specific interrupts contains the mailbox interrupts of each core the pending mailbox interrupts are moved to the
+/// bits of the current core.
+fn map_core_pending(core: usize, pending: u32) -> u32 {
+  let mut mapped = pending & CORE_PENDING_MASK;
+  for mailbox in 0..4 {
+    if pending & (1 << (4 + mailbox)) != 0 {
+      let irq = Interrupt::core_mailbox(core, mailbox);
+      mapped |= 1 << (irq as u32 & 0x1F);
+    }
+  }
+  mapped
+}
+
+/// The core timer, GPU, PMU, AXI and local timer interrupts keep their bits of the core specific pending register. The
+/// GPU bit is only used to read the GPU banks and is ignored by the dispatching of the interrupts.
+const CORE_PENDING_MASK: u32 = 0xF0F;
+
+/// Activate an interrupt to be raised as FIQ. The Raspberry Pi3 allows only one GPU interrupt to be raised as FIQ.
+/// Activating a further one replaces the previous GPU interrupt. On the Raspberry Pi4 the GPU interrupts are raised
+/// as FIQ on the core the GPU interrupts are routed to. The core timer interrupts are raised as FIQ on core 0 and the
+/// mailbox interrupts on the core they belong to.
+pub(crate) fn activate_fiq(irq: Interrupt) {
+  let bank = (irq as u32) >> 5;
+  let bit = (irq as u32) & 0x1F;
+  match bank {
+    #[cfg(feature = "pi3")]
+    0..=2 => FIQ_CONTROL::Register.set(FIQ_CONTROL_ENABLE | irq as u32),
+    #[cfg(any(feature = "pi4_low", feature = "pi4_high"))]
+    0..=2 => {
+      let core = gpu_routing(RoutingMode::Fiq);
+      armc_write(ARMC_SET_EN, core, RoutingMode::Fiq, bank as usize, 1 << bit);
+    }
+    3 => match irq {
+      Interrupt::CntPsIrq | Interrupt::CntPnsIrq | Interrupt::CntHpIrq | Interrupt::CntVIrq => {
+        modify_core_timer_irq(0, bit + CORE_TIMER_FIQ_SHIFT, true);
+      }
+      // the mailbox interrupts are enabled in the mailbox interrupt control register of their core
+      _ => {
+        if let Some((core, mailbox)) = irq.mailbox() {
+          modify_mailbox_int(core, mailbox + MAILBOX_FIQ_SHIFT, true);
+        }
+      }
+    },
+    _ => (),
+  }
+}
+
+pub(crate) fn deactivate_fiq(irq: Interrupt) {
+  let bank = (irq as u32) >> 5;
+  let bit = (irq as u32) & 0x1F;
+  match bank {
+    // only disable the FIQ if it is currently selected for the given interrupt
+    #[cfg(feature = "pi3")]
+    0..=2 => {
+      if FIQ_CONTROL::Register.get() == FIQ_CONTROL_ENABLE | irq as u32 {
+        FIQ_CONTROL::Register.set(0);
+      }
+    }
+    #[cfg(any(feature = "pi4_low", feature = "pi4_high"))]
+    0..=2 => {
+      let core = gpu_routing(RoutingMode::Fiq);
+      armc_write(ARMC_CLR_EN, core, RoutingMode::Fiq, bank as usize, 1 << bit);
+    }
+    3 => match irq {
+      Interrupt::CntPsIrq | Interrupt::CntPnsIrq | Interrupt::CntHpIrq | Interrupt::CntVIrq => {
+        modify_core_timer_irq(0, bit + CORE_TIMER_FIQ_SHIFT, false);
+      }
+      // the mailbox interrupts are disabled in the mailbox interrupt control register of their core
+      _ => {
+        if let Some((core, mailbox)) = irq.mailbox() {
+          modify_mailbox_int(core, mailbox + MAILBOX_FIQ_SHIFT, false);
+        }
+      }
+    },
+    _ => (),
+  }
+}
+
+/// Retrieve the pending fast interrupts of the current core.
+pub fn get_pending_fiqs() -> [u32; 4] {
+  let core = core_id();
+  let core_pending = core_fiq_pending(core);
+  #[cfg(any(feature = "pi4_low", feature = "pi4_high"))]
+  {
+    let mut pendings = [0, 0, 0, core_pending];
+    for (bank, pending) in pendings.iter_mut().take(3).enumerate() {
+      *pending = armc_read(ARMC_PENDING, core, RoutingMode::Fiq, bank)
+        & armc_read(ARMC_SET_EN, core, RoutingMode::Fiq, bank);
+    }
+    pendings
+  }
+  #[cfg(feature = "pi3")]
+  {
+    // there is only one GPU interrupt that could be raised as FIQ. The one selected in the FIQ control register
+    let mut pendings = [0, 0, 0, core_pending];
+    let control = FIQ_CONTROL::Register.get();
+    if core_pending & CORE_GPU_PENDING != 0 && control & FIQ_CONTROL_ENABLE != 0 {
+      let source = control & !FIQ_CONTROL_ENABLE;
+      pendings[(source >> 5) as usize] |= 1 << (source & 0x1F);
+    }
+    pendings
+  }
+}
+
+/// The FIQ enable bits in the core timer interrupt control register follow the IRQ enable bits
+const CORE_TIMER_FIQ_SHIFT: u32 = 4;
+
+/// The enable bit of the FIQ control register
+#[cfg(feature = "pi3")]
+const FIQ_CONTROL_ENABLE: u32 = 1 << 7;
+
+/// The legacy interrupt controller does not require any completion of an interrupt. The interrupt is cleared at the
+/// source by the handler acknowledging it.
+pub(crate) fn end_of_interrupt() {}
+
+/// Disable the given GPU interrupts of the banks 0..2 that are currently enabled as IRQ. The interrupts that have been
+/// disabled are returned to enable them again with [unmask_gpu_irqs].
+#[cfg(feature = "nesting")]
+pub(crate) fn mask_gpu_irqs(mask: [u32; 3]) -> [u32; 3] {
+  #[cfg(feature = "pi3")]
+  let masked = {
+    let masked = [
+      IRQ0_ENABLE_1::Register.get() & mask[0],
+      IRQ0_ENABLE_2::Register.get() & mask[1],
+      IRQ0_ENABLE_B::Register.get() & mask[2],
+    ];
+    IRQ0_DISABLE_1::Register.set(masked[0]);
+    IRQ0_DISABLE_2::Register.set(masked[1]);
+    IRQ0_DISABLE_B::Register.set(masked[2]);
+    masked
+  };
+  #[cfg(any(feature = "pi4_low", feature = "pi4_high"))]
+  let masked = {
+    let core = gpu_routing(RoutingMode::Irq);
+    let mut masked = [0; 3];
+    for (bank, masked) in masked.iter_mut().enumerate() {
+      *masked = armc_read(ARMC_SET_EN, core, RoutingMode::Irq, bank) & mask[bank];
+      armc_write(ARMC_CLR_EN, core, RoutingMode::Irq, bank, *masked);
+    }
+    masked
+  };
+  masked
+}
+
+/// Enable the GPU interrupts again that have been disabled with [mask_gpu_irqs].
+#[cfg(feature = "nesting")]
+pub(crate) fn unmask_gpu_irqs(masked: [u32; 3]) {
+  #[cfg(feature = "pi3")]
+  {
+    IRQ0_ENABLE_1::Register.set(masked[0]);
+    IRQ0_ENABLE_2::Register.set(masked[1]);
+    IRQ0_ENABLE_B::Register.set(masked[2]);
+  }
+  #[cfg(any(feature = "pi4_low", feature = "pi4_high"))]
+  {
+    let core = gpu_routing(RoutingMode::Irq);
+    for (bank, &masked) in masked.iter().enumerate() {
+      armc_write(ARMC_SET_EN, core, RoutingMode::Irq, bank, masked);
+    }
+  }
+}
+
+// Define the interrupt configuration register for the Raspberry Pi3
+#[cfg(feature = "pi3")]
+define_mmio_register! [
+    /// Routing of the GPU interrupts to the cores
+    GPU_INT_ROUTING<ReadWrite<u32>@(ARM_CORE_BASE + 0x00C)> {
+      IRQ_CORE OFFSET(0) BITS(2),
+      FIQ_CORE OFFSET(2) BITS(2)
+    },
+
+    /// Basic pending interrupts 7..19, 53..57 and 62 in bits 10..20
+    IRQ0_PENDING_B<ReadWrite<u32>@(ARM_IRQ_BASE + 0x200)> {
+      ARM_TIMER_IRQ OFFSET(0),
+      ARM_MAILBOX_IRQ0 OFFSET(1),
+      ARM_DOORBELL_IRQ0 OFFSET(2),
+      ARM_DOORBELL_IRQ1 OFFSET(3),
+      VPU_C0_C1_HALT OFFSET(4),
+      VPU_C1_HALT OFFSET(5),
+      ARM_ILLEGAL_ACCESS1 OFFSET(6),
+      ARM_ILLEGAL_ACCESS0 OFFSET(7),
+      PENDING2 OFFSET(8),
+      PENDING1 OFFSET(9)
+    },
+    /// Pending interrupts 0 .. 31
+    IRQ0_PENDING_1<ReadWrite<u32>@(ARM_IRQ_BASE + 0x204)>,
+    /// Pending interrupts 32 .. 63
+    IRQ0_PENDING_2<ReadWrite<u32>@(ARM_IRQ_BASE + 0x208)>,
+
+    /// Select the GPU interrupt that is raised as FIQ
+    FIQ_CONTROL<ReadWrite<u32>@(ARM_IRQ_BASE + 0x20C)> {
+      SOURCE OFFSET(0) BITS(7),
+      ENABLE OFFSET(7)
+    },
+
+    IRQ0_ENABLE_1<ReadWrite<u32>@(ARM_IRQ_BASE + 0x210)>,
+    IRQ0_ENABLE_2<ReadWrite<u32>@(ARM_IRQ_BASE + 0x214)>,
+    IRQ0_ENABLE_B<ReadWrite<u32>@(ARM_IRQ_BASE + 0x218)>,
+
+    IRQ0_DISABLE_1<ReadWrite<u32>@(ARM_IRQ_BASE + 0x21C)>,
+    IRQ0_DISABLE_2<ReadWrite<u32>@(ARM_IRQ_BASE + 0x220)>,
+    IRQ0_DISABLE_B<ReadWrite<u32>@(ARM_IRQ_BASE + 0x224)>
+];
+
+/// Offsets of the pending, enable and disable register sets of the Raspberry Pi4 legacy interrupt controller
+#[cfg(any(feature = "pi4_low", feature = "pi4_high"))]
+const ARMC_PENDING: usize = 0x200;
+#[cfg(any(feature = "pi4_low", feature = "pi4_high"))]
+const ARMC_SET_EN: usize = 0x210;
+#[cfg(any(feature = "pi4_low", feature = "pi4_high"))]
+const ARMC_CLR_EN: usize = 0x220;
+
+/// The cores the GPU IRQ's and the GPU FIQ's are routed to on the Raspberry Pi4. Like on the Raspberry Pi3 both are
+/// routed independently.
+#[cfg(any(feature = "pi4_low", feature = "pi4_high"))]
+static GPU_ROUTING: [AtomicUsize; 2] = [AtomicUsize::new(0), AtomicUsize::new(0)];
+
+#[cfg(any(feature = "pi4_low", feature = "pi4_high"))]
+fn gpu_routing(mode: RoutingMode) -> usize {
+  GPU_ROUTING[mode as usize].load(Ordering::Acquire)
+}
+
+/// Get the address of a register of the Raspberry Pi4 legacy interrupt controller. There is a set of pending, enable
+/// and disable registers for each core and for IRQ and FIQ. Each of them consists of three registers covering the VC
+/// interrupts 0..31, 32..63 and the ARM interrupts 64..79.
+#[cfg(any(feature = "pi4_low", feature = "pi4_high"))]
+fn armc_register(offset: usize, core: usize, mode: RoutingMode, bank: usize) -> usize {
+  let mode_offset = match mode {
+    RoutingMode::Irq => 0x000,
+    RoutingMode::Fiq => 0x100,
+  };
+  ARM_IRQ_BASE + offset + mode_offset + core * 0x40 + bank * 4
+}
+
+#[cfg(any(feature = "pi4_low", feature = "pi4_high"))]
+fn armc_read(offset: usize, core: usize, mode: RoutingMode, bank: usize) -> u32 {
+  unsafe { read_volatile(armc_register(offset, core, mode, bank) as *const u32) }
+}
+
+#[cfg(any(feature = "pi4_low", feature = "pi4_high"))]
+fn armc_write(offset: usize, core: usize, mode: RoutingMode, bank: usize, value: u32) {
+  unsafe { write_volatile(armc_register(offset, core, mode, bank) as *mut u32, value) };
+}
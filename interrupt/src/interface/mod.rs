@@ -0,0 +1,639 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # Internal interrupt interface implementation
+//!
+//! There are some siginificant differences between the RPi3 and RP4 with respect to the interrupt
+//! configuration and available registers. The RPi4 introduces a GIC-400 interrupt controller.
+//! However, the RPi4 could be run with the "legacy" interrupt controller that has been available
+//! within the RPi3 already. However, running in NO-GIC mode still need to consider the different
+//! register structure.
+//!
+//! The GIC-400 allows to route interrupts to any core. Thus the interrupt enable and pending register
+//! exists 4 times (once for each core). Using the legacy mode there exist some core specific interrupts that are
+//! routed to the corresponding core and core independent interrupts that are routed based on a specific
+//! GPU Interrupts Routing register which core should receive those interrupts.
+//!
+//! The RPi4 runs in legacy mode unless the `pi4_gic` feature is active. In this case the GIC-400 distributor and CPU
+//! interface are used to enable, acknowledge and complete the interrupts.
+//!
+//! Each backend provides the same set of functions to the crate: `initialize`, `initialize_core`, `activate`,
+//! `deactivate`, `get_pending_irqs` and `end_of_interrupt` as well as `activate_fiq`, `deactivate_fiq` and
+//! `get_pending_fiqs` for the interrupts raised as FIQ. The legacy interrupt controller provides `mask_gpu_irqs` and
+//! `unmask_gpu_irqs` for the software interrupt nesting of the `nesting` feature. The core mailboxes are accessed with
+//! `mailbox_set`, `mailbox_read` and `mailbox_clear` the same way for all backends. The legacy interrupt controller and
+//! the GIC enable the mailbox interrupts with `modify_mailbox_int`. The local timer is configured with
+//! `set_local_timer_reload`, `enable_local_timer` and `acknowledge_local_timer` for all backends while each backend
+//! provides `route_local_timer`.
+//!
+//! With the `sim` feature the MMIO registers are replaced by an in-memory model of the legacy interrupt controller to
+//! run the interrupt handling on the host, e.g. for testing. The model also provides the interrupt mask bits of the
+//! ``DAIF`` register.
+//!
+
+#[cfg(not(feature = "sim"))]
+use ruspiro_arch_aarch64::register::el1::mpidr_el1;
+#[cfg(not(feature = "sim"))]
+use ruspiro_mmio_register::define_mmio_register;
+
+#[cfg(not(any(feature = "pi4_gic", feature = "sim")))]
+mod legacy;
+#[cfg(not(any(feature = "pi4_gic", feature = "sim")))]
+pub(crate) use legacy::*;
+
+#[cfg(all(feature = "pi4_gic", not(feature = "sim")))]
+mod gic;
+#[cfg(all(feature = "pi4_gic", not(feature = "sim")))]
+pub(crate) use gic::*;
+
+#[cfg(feature = "sim")]
+pub mod sim;
+#[cfg(feature = "sim")]
+pub(crate) use sim::*;
+
+#[cfg(feature = "pi3")]
+#[cfg_attr(feature = "sim", allow(dead_code))]
+const PERIPHERAL_BASE: usize = 0x0_3F00_0000;
+#[cfg(feature = "pi4_low")]
+#[cfg_attr(feature = "sim", allow(dead_code))]
+const PERIPHERAL_BASE: usize = 0x0_FE00_0000;
+#[cfg(feature = "pi4_high")]
+#[cfg_attr(feature = "sim", allow(dead_code))]
+const PERIPHERAL_BASE: usize = 0x4_7E00_0000;
+
+#[cfg(feature = "pi3")]
+#[cfg_attr(feature = "sim", allow(dead_code))]
+const ARM_CORE_BASE: usize = 0x0_4000_0000;
+#[cfg(feature = "pi4_low")]
+#[cfg_attr(feature = "sim", allow(dead_code))]
+const ARM_CORE_BASE: usize = 0x0_FF80_0000;
+#[cfg(feature = "pi4_high")]
+#[cfg_attr(feature = "sim", allow(dead_code))]
+const ARM_CORE_BASE: usize = 0x4_C000_0000;
+
+#[cfg_attr(any(feature = "pi4_gic", feature = "sim"), allow(dead_code))]
+const ARM_IRQ_BASE: usize = PERIPHERAL_BASE + 0xB000;
+
+/// Get the number of the core executing this function
+#[cfg(not(feature = "sim"))]
+pub(crate) fn core_id() -> usize {
+  (mpidr_el1::read(mpidr_el1::AFF0::Field).value() & 0x3) as usize
+}
+
+/// Read the physical count of the generic timer (``CNTPCT_EL0``)
+#[cfg(not(feature = "sim"))]
+#[cfg_attr(not(feature = "stats"), allow(dead_code))]
+pub(crate) fn counter() -> u64 {
+  #[allow(unused_mut)]
+  let mut count = 0;
+  // the ISB ensures the counter is not read ahead of the preceeding instructions
+  #[cfg(target_arch = "aarch64")]
+  unsafe {
+    asm!(
+      "isb
+       mrs {}, cntpct_el0",
+      out(reg) count
+    )
+  };
+  count
+}
+
+/// The write-set registers of the core mailboxes. Each core has 4 mailboxes with consecutive registers
+#[cfg(not(feature = "sim"))]
+const CORE_MB_SET: usize = ARM_CORE_BASE + 0x080;
+/// The read-and-clear registers of the core mailboxes. Writing 1 to a bit clears it
+#[cfg(not(feature = "sim"))]
+const CORE_MB_RDCLR: usize = ARM_CORE_BASE + 0x0C0;
+
+/// Set the bits of the given value in the mailbox of the given core. The mailbox raises it's interrupt as long as any of
+/// it's bits is set.
+#[cfg(not(feature = "sim"))]
+pub(crate) fn mailbox_set(core: usize, mailbox: usize, value: u32) {
+  let register = mailbox_register(CORE_MB_SET, core, mailbox);
+  unsafe { core::ptr::write_volatile(register, value) };
+}
+
+/// Read the content of the mailbox of the given core
+#[cfg(not(feature = "sim"))]
+pub(crate) fn mailbox_read(core: usize, mailbox: usize) -> u32 {
+  let register = mailbox_register(CORE_MB_RDCLR, core, mailbox);
+  unsafe { core::ptr::read_volatile(register) }
+}
+
+/// Clear the bits of the given value in the mailbox of the given core
+#[cfg(not(feature = "sim"))]
+pub(crate) fn mailbox_clear(core: usize, mailbox: usize, value: u32) {
+  let register = mailbox_register(CORE_MB_RDCLR, core, mailbox);
+  unsafe { core::ptr::write_volatile(register, value) };
+}
+
+/// Get the register of the given mailbox of the core within the list of mailbox registers starting at the given base
+#[cfg(not(feature = "sim"))]
+fn mailbox_register(base: usize, core: usize, mailbox: usize) -> *mut u32 {
+  (base + (core << 4) + (mailbox << 2)) as *mut u32
+}
+
+/// Set or clear the given bit in the mailbox interrupt control register of the given core. The bits 0..3 enable the
+/// mailboxes 0..3 to raise an IRQ and the bits 4..7 to raise a FIQ.
+#[cfg(not(feature = "sim"))]
+pub(crate) fn modify_mailbox_int(core: usize, bit: usize, set: bool) {
+  let update = |value: u32| {
+    if set {
+      value | (1 << bit)
+    } else {
+      value & !(1 << bit)
+    }
+  };
+  match core {
+    0 => CORE0_MB_INT_CNTRL::Register.set(update(CORE0_MB_INT_CNTRL::Register.get())),
+    1 => CORE1_MB_INT_CNTRL::Register.set(update(CORE1_MB_INT_CNTRL::Register.get())),
+    2 => CORE2_MB_INT_CNTRL::Register.set(update(CORE2_MB_INT_CNTRL::Register.get())),
+    _ => CORE3_MB_INT_CNTRL::Register.set(update(CORE3_MB_INT_CNTRL::Register.get())),
+  }
+}
+
+/// The FIQ enable bits in the mailbox interrupt control register follow the IRQ enable bits
+#[cfg(not(feature = "sim"))]
+pub(crate) const MAILBOX_FIQ_SHIFT: usize = 4;
+
+/// The largest reload value of the local timer, the reload value occupies the bits 0..27 of the control register
+pub(crate) const LOCAL_TIMER_MAX_RELOAD: u32 = 0x0FFF_FFFF;
+/// The bit of the local timer control register that starts the timer
+#[cfg(not(feature = "sim"))]
+const LOCAL_TIMER_ENABLE: u32 = 1 << 28;
+/// The bit of the local timer clear and reload register that clears the interrupt flag
+#[cfg(not(feature = "sim"))]
+const LOCAL_TIMER_CLEAR: u32 = 1 << 31;
+
+/// Set the value the local timer counts down from
+#[cfg(not(feature = "sim"))]
+pub(crate) fn set_local_timer_reload(reload: u32) {
+  let ctrl = LOCAL_TIMER_CTRL::Register.get();
+  LOCAL_TIMER_CTRL::Register.set((ctrl & !LOCAL_TIMER_MAX_RELOAD) | reload);
+}
+
+/// Start or stop the local timer
+#[cfg(not(feature = "sim"))]
+pub(crate) fn enable_local_timer(enable: bool) {
+  let ctrl = LOCAL_TIMER_CTRL::Register.get();
+  if enable {
+    LOCAL_TIMER_CTRL::Register.set(ctrl | LOCAL_TIMER_ENABLE);
+  } else {
+    LOCAL_TIMER_CTRL::Register.set(ctrl & !LOCAL_TIMER_ENABLE);
+  }
+}
+
+/// Clear the interrupt flag of the local timer. The timer keeps counting down from the reload value.
+#[cfg(not(feature = "sim"))]
+pub(crate) fn acknowledge_local_timer() {
+  LOCAL_TIMER_IRQ_CLR::Register.set(LOCAL_TIMER_CLEAR);
+}
+
+/// The ``IRQ`` mask bit of the ``DAIF`` register
+pub(crate) const DAIF_IRQ: u64 = 1 << 7;
+/// The ``FIQ`` mask bit of the ``DAIF`` register
+pub(crate) const DAIF_FIQ: u64 = 1 << 6;
+
+/// globally enable ``IRQ`` interrupts to be triggered
+#[cfg(not(feature = "sim"))]
+pub(crate) fn enable_irq() {
+  #[cfg(target_arch = "aarch64")]
+  unsafe {
+    asm!(
+      "msr daifclr, #2
+            isb"
+    ) // as per ARM spec the ISB ensures triggering pending interrupts
+  };
+}
+
+/// globally enable ``FIQ`` interrupts to be triggered
+#[cfg(not(feature = "sim"))]
+pub(crate) fn enable_fiq() {
+  #[cfg(target_arch = "aarch64")]
+  unsafe {
+    asm!(
+      "msr daifclr, #1
+            isb"
+    ) // as per ARM spec the ISB ensures triggering pending interrupts
+  };
+}
+
+/// globally disable ``IRQ`` interrupts from beeing triggered.
+#[cfg(not(feature = "sim"))]
+pub fn disable_irq() {
+  #[cfg(target_arch = "aarch64")]
+  unsafe {
+    asm!("msr daifset, #2")
+  };
+}
+
+/// globally disable ``FIQ`` interrupts from beeing triggered.
+#[cfg(not(feature = "sim"))]
+pub fn disable_fiq() {
+  #[cfg(target_arch = "aarch64")]
+  unsafe {
+    asm!("msr daifset, #1")
+  };
+}
+
+/// Read the interrupt mask bits of the current core (``DAIF``)
+#[cfg(not(feature = "sim"))]
+pub(crate) fn daif() -> u64 {
+  #[allow(unused_mut)]
+  let mut daif = DAIF_IRQ | DAIF_FIQ;
+  #[cfg(target_arch = "aarch64")]
+  unsafe {
+    asm!("mrs {}, daif", out(reg) daif)
+  };
+  daif
+}
+
+/// Write the interrupt mask bits of the current core (``DAIF``)
+#[cfg(not(feature = "sim"))]
+#[cfg_attr(not(target_arch = "aarch64"), allow(unused_variables))]
+pub(crate) fn set_daif(daif: u64) {
+  #[cfg(target_arch = "aarch64")]
+  unsafe {
+    asm!(
+      "msr daif, {}
+       isb",
+      in(reg) daif
+    ) // as per ARM spec the ISB ensures triggering pending interrupts
+  };
+}
+
+/// Read the exception link register and the saved program status register (``ELR_EL1`` and ``SPSR_EL1``) of the
+/// interrupt currently handled. An interrupt preempting the handler overwrites them.
+#[cfg(all(feature = "nesting", not(feature = "sim")))]
+pub(crate) fn exception_state() -> (u64, u64) {
+  #[allow(unused_mut)]
+  let mut state = (0, 0);
+  #[cfg(target_arch = "aarch64")]
+  unsafe {
+    asm!(
+      "mrs {}, elr_el1
+       mrs {}, spsr_el1",
+      out(reg) state.0,
+      out(reg) state.1
+    )
+  };
+  state
+}
+
+/// Restore the exception link register and the saved program status register read with [exception_state]
+#[cfg(all(feature = "nesting", not(feature = "sim")))]
+#[cfg_attr(not(target_arch = "aarch64"), allow(unused_variables))]
+pub(crate) fn set_exception_state(state: (u64, u64)) {
+  #[cfg(target_arch = "aarch64")]
+  unsafe {
+    asm!(
+      "msr elr_el1, {}
+       msr spsr_el1, {}",
+      in(reg) state.0,
+      in(reg) state.1
+    )
+  };
+}
+
+// Define the interrupt configuration register common between Raspberry Pi3 and Pi4
+#[cfg(not(feature = "sim"))]
+define_mmio_register![
+  LOCAL_TIMER_CTRL<ReadWrite<u32>@(ARM_CORE_BASE + 0x034)> {
+    RELOAD OFFSET(0) BITS(28),
+    ENABLE OFFSET(28),
+    IRQ_ENABLE OFFSET(29) [
+      ENABLED = 1,
+      DISABLED = 0
+    ]
+  },
+  /// Clear the interrupt flag of the local timer and reload it
+  LOCAL_TIMER_IRQ_CLR<WriteOnly<u32>@(ARM_CORE_BASE + 0x038)> {
+    RELOAD OFFSET(30),
+    CLEAR OFFSET(31)
+  },
+  /// Routing of the local timer interrupt to the IRQ or FIQ of a core
+  LOCAL_TIMER_ROUTING<ReadWrite<u32>@(ARM_CORE_BASE + 0x024)> {
+    CORE OFFSET(0) BITS(2),
+    FIQ OFFSET(2)
+  },
+    /// Core timer interrupts are available on each core.
+    CORE0_TIMER_IRQ<ReadWrite<u32>@(ARM_CORE_BASE + 0x040)> {
+      CNTPSIRQ OFFSET(0) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTPNSIRQ OFFSET(1) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTHPIRQ OFFSET(2) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTVIRQ OFFSET(3) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTPSFIQ OFFSET(4) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTPNSFIQ OFFSET(5) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTHPFIQ OFFSET(6) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTVFIQ OFFSET(7) [
+        ENABLED = 1,
+        DISABLED = 0
+      ]
+    },
+
+    /// Core timer interrupts are available on each core.
+    CORE1_TIMER_IRQ<ReadWrite<u32>@(ARM_CORE_BASE + 0x044)> {
+      CNTPSIRQ OFFSET(0) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTPNSIRQ OFFSET(1) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTHPIRQ OFFSET(2) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTVIRQ OFFSET(3) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTPSFIQ OFFSET(4) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTPNSFIQ OFFSET(5) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTHPFIQ OFFSET(6) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTVFIQ OFFSET(7) [
+        ENABLED = 1,
+        DISABLED = 0
+      ]
+    },
+
+    /// Core timer interrupts are available on each core.
+    CORE2_TIMER_IRQ<ReadWrite<u32>@(ARM_CORE_BASE + 0x048)> {
+      CNTPSIRQ OFFSET(0) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTPNSIRQ OFFSET(1) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTHPIRQ OFFSET(2) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTVIRQ OFFSET(3) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTPSFIQ OFFSET(4) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTPNSFIQ OFFSET(5) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTHPFIQ OFFSET(6) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTVFIQ OFFSET(7) [
+        ENABLED = 1,
+        DISABLED = 0
+      ]
+    },
+
+    /// Core timer interrupts are available on each core.
+    CORE3_TIMER_IRQ<ReadWrite<u32>@(ARM_CORE_BASE + 0x04C)> {
+      CNTPSIRQ OFFSET(0) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTPNSIRQ OFFSET(1) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTHPIRQ OFFSET(2) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTVIRQ OFFSET(3) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTPSFIQ OFFSET(4) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTPNSFIQ OFFSET(5) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTHPFIQ OFFSET(6) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      CNTVFIQ OFFSET(7) [
+        ENABLED = 1,
+        DISABLED = 0
+      ]
+    },
+
+  /// Core Mailbox interrupt control for ARM core 0
+  CORE0_MB_INT_CNTRL<ReadWrite<u32>@(ARM_CORE_BASE + 0x050)> {
+    MB0_IRQ OFFSET(0) [
+      ENABLED = 1,
+      DISABLED = 0
+    ],
+    MB1_IRQ OFFSET(1) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      MB2_IRQ OFFSET(2) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      MB3_IRQ OFFSET(3) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      MB0_FIQ OFFSET(4) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      MB1_FIQ OFFSET(5) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      MB2_FIQ OFFSET(6) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      MB3_FIQ OFFSET(7) [
+        ENABLED = 1,
+        DISABLED = 0
+      ]
+  },
+  /// Core Mailbox interrupt control for ARM core 1
+  CORE1_MB_INT_CNTRL<ReadWrite<u32>@(ARM_CORE_BASE + 0x054)> {
+    MB0_IRQ OFFSET(0) [
+      ENABLED = 1,
+      DISABLED = 0
+    ],
+    MB1_IRQ OFFSET(1) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      MB2_IRQ OFFSET(2) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      MB3_IRQ OFFSET(3) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      MB0_FIQ OFFSET(4) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      MB1_FIQ OFFSET(5) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      MB2_FIQ OFFSET(6) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      MB3_FIQ OFFSET(7) [
+        ENABLED = 1,
+        DISABLED = 0
+      ]
+  },
+  /// Core Mailbox interrupt control for ARM core 2
+  CORE2_MB_INT_CNTRL<ReadWrite<u32>@(ARM_CORE_BASE + 0x058)> {
+    MB0_IRQ OFFSET(0) [
+      ENABLED = 1,
+      DISABLED = 0
+    ],
+    MB1_IRQ OFFSET(1) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      MB2_IRQ OFFSET(2) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      MB3_IRQ OFFSET(3) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      MB0_FIQ OFFSET(4) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      MB1_FIQ OFFSET(5) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      MB2_FIQ OFFSET(6) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      MB3_FIQ OFFSET(7) [
+        ENABLED = 1,
+        DISABLED = 0
+      ]
+  },
+  /// Core Mailbox interrupt control for ARM core 3
+  CORE3_MB_INT_CNTRL<ReadWrite<u32>@(ARM_CORE_BASE + 0x05C)> {
+    MB0_IRQ OFFSET(0) [
+      ENABLED = 1,
+      DISABLED = 0
+    ],
+    MB1_IRQ OFFSET(1) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      MB2_IRQ OFFSET(2) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      MB3_IRQ OFFSET(3) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      MB0_FIQ OFFSET(4) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      MB1_FIQ OFFSET(5) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      MB2_FIQ OFFSET(6) [
+        ENABLED = 1,
+        DISABLED = 0
+      ],
+      MB3_FIQ OFFSET(7) [
+        ENABLED = 1,
+        DISABLED = 0
+      ]
+  },
+    /// ARM core interrupt source for core 0
+  CORE0_IRQ_PENDING<ReadWrite<u32>@(ARM_CORE_BASE + 0x060)> {
+    MB3_IRQ OFFSET(7)
+  },
+  /// ARM core interrupt source for core 1
+  CORE1_IRQ_PENDING<ReadWrite<u32>@(ARM_CORE_BASE + 0x064)> {
+    MB3_IRQ OFFSET(7)
+  },
+  /// ARM core interrupt source for core 2
+  CORE2_IRQ_PENDING<ReadWrite<u32>@(ARM_CORE_BASE + 0x068)> {
+    MB3_IRQ OFFSET(7)
+  },
+  /// ARM core interrupt source for core 3
+  CORE3_IRQ_PENDING<ReadWrite<u32>@(ARM_CORE_BASE + 0x06C)> {
+    MB3_IRQ OFFSET(7)
+  },
+  /// ARM core fast interrupt source for core 0
+  CORE0_FIQ_PENDING<ReadWrite<u32>@(ARM_CORE_BASE + 0x070)> {
+    MB3_FIQ OFFSET(7)
+  },
+  /// ARM core fast interrupt source for core 1
+  CORE1_FIQ_PENDING<ReadWrite<u32>@(ARM_CORE_BASE + 0x074)> {
+    MB3_FIQ OFFSET(7)
+  },
+  /// ARM core fast interrupt source for core 2
+  CORE2_FIQ_PENDING<ReadWrite<u32>@(ARM_CORE_BASE + 0x078)> {
+    MB3_FIQ OFFSET(7)
+  },
+  /// ARM core fast interrupt source for core 3
+  CORE3_FIQ_PENDING<ReadWrite<u32>@(ARM_CORE_BASE + 0x07C)> {
+    MB3_FIQ OFFSET(7)
+  }
+];
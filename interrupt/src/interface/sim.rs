@@ -0,0 +1,679 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # Simulated interrupt controller
+//!
+//! With the `sim` feature the MMIO registers of the legacy interrupt controller are replaced by an in-memory model.
+//! This allows the interrupt handling of this crate to be tested on the host. The model keeps the enabled and the
+//! pending state of the GPU interrupts (bank 0..2) and of the core specific interrupts (bank 3) of each core for IRQ's
+//! and FIQ's separately. The GPU IRQ's and FIQ's are routed to a core individually like on the Raspberry Pi3.
+//!
+//! Tests raise and clear the pending state of the interrupts with the functions of this module and call the exported
+//! `__isr_default` or `__fiq_default` function like the exception vector would do. A raised interrupt stays pending
+//! until it is cleared, like a level triggered interrupt that is not acknowledged by it's handler.
+//!
+//! The interrupt mask bits of the ``DAIF`` register are simulated as well. Like after a reset of the core IRQ's and
+//! FIQ's are masked until they are enabled. The physical count of the generic timer is simulated as well. It only
+//! changes if it is set or advanced with the functions of this module, so the timing of a handler could be simulated
+//! by advancing the counter within it.
+//!
+//! The event detect status and enable registers of the GPIO block are simulated as well. An event detected on a GPIO
+//! pin is simulated with [raise_gpio] and keeps the interrupt line of it's bank pending until it is acknowledged.
+//!
+//! The DMA interrupt status register is simulated as well. The interrupt of a DMA channel is raised with [raise_dma]
+//! that keeps the interrupt line of the channel pending until it is cleared for all channels sharing the line.
+//!
+//! The mailboxes of the cores are simulated as well. Sending an inter-processor interrupt sets the bits of the message
+//! in the mailbox of the target core and keeps it's mailbox interrupt pending until the mailbox is cleared again.
+//!
+//! The model provides the pending interrupts already mapped to the banks the dispatching expects. The register layout
+//! of the real interrupt controllers is not part of it. So the mapping of the legacy interrupt controller from it's
+//! registers to the banks, e.g. of the core specific pending register or of the per core banks of the Raspberry Pi4,
+//! and the GIC are not covered by the tests running against the model.
+//!
+//! The state of the model is shared between all threads. Tests running in parallel need to be serialized if they
+//! raise interrupts or rely on the activation state.
+//!
+//! ```no_run
+//! # use ruspiro_interrupt::{self as irq, sim, Interrupt};
+//! irq::initialize();
+//! irq::activate(Interrupt::ArmTimer, None);
+//! sim::raise(Interrupt::ArmTimer);
+//! extern "C" {
+//!   fn __isr_default();
+//! }
+//! unsafe { __isr_default() };
+//! ```
+//!
+
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+
+use super::{DAIF_FIQ, DAIF_IRQ, LOCAL_TIMER_MAX_RELOAD};
+use crate::{
+  dmahandler::DMA_LINES,
+  gpiohandler::{GPAFEN0, GPAREN0, GPEDS0, GPFEN0, GPHEN0, GPIO_BANK_PINS, GPLEN0, GPREN0},
+  AuxDevice, GpioEdge, Interrupt, RoutingMode,
+};
+
+/// The core the interrupt dispatching is simulated on
+static CURRENT_CORE: AtomicUsize = AtomicUsize::new(0);
+
+/// The pending GPU interrupts of the banks 0..2
+static GPU_PENDING: [AtomicU32; 3] = [AtomicU32::new(0), AtomicU32::new(0), AtomicU32::new(0)];
+
+/// The GPU interrupts enabled as IRQ (index 0) and FIQ (index 1)
+static GPU_ENABLED: [[AtomicU32; 3]; 2] = [
+  [AtomicU32::new(0), AtomicU32::new(0), AtomicU32::new(0)],
+  [AtomicU32::new(0), AtomicU32::new(0), AtomicU32::new(0)],
+];
+
+/// The core the GPU IRQ's (index 0) and FIQ's (index 1) are routed to
+static GPU_ROUTING: [AtomicUsize; 2] = [AtomicUsize::new(0), AtomicUsize::new(0)];
+
+/// The pending core specific interrupts of each core using the bit positions of the core interrupt bank
+static CORE_PENDING: [AtomicU32; 4] = [
+  AtomicU32::new(0),
+  AtomicU32::new(0),
+  AtomicU32::new(0),
+  AtomicU32::new(0),
+];
+
+/// The core specific interrupts of each core enabled as IRQ (index 0) and FIQ (index 1)
+static CORE_ENABLED: [[AtomicU32; 4]; 2] = [
+  [
+    AtomicU32::new(0),
+    AtomicU32::new(0),
+    AtomicU32::new(0),
+    AtomicU32::new(0),
+  ],
+  [
+    AtomicU32::new(0),
+    AtomicU32::new(0),
+    AtomicU32::new(0),
+    AtomicU32::new(0),
+  ],
+];
+
+/// The simulated interrupt mask bits of the ``DAIF`` register
+static DAIF: AtomicU64 = AtomicU64::new(DAIF_IRQ | DAIF_FIQ);
+
+/// The simulated exception link register (index 0) and saved program status register (index 1)
+static EXCEPTION_STATE: [AtomicU64; 2] = [AtomicU64::new(0), AtomicU64::new(0)];
+
+/// The simulated physical count of the generic timer
+static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// The simulated AUX interrupt status register
+static AUX_PENDING: AtomicU32 = AtomicU32::new(0);
+
+/// The simulated mailboxes 0..3 of each core
+static MAILBOXES: [[AtomicU32; 4]; 4] = {
+  // the constants are only used as the initial value of the mailboxes
+  #[allow(clippy::declare_interior_mutable_const)]
+  const EMPTY: AtomicU32 = AtomicU32::new(0);
+  #[allow(clippy::declare_interior_mutable_const)]
+  const CORE_MAILBOXES: [AtomicU32; 4] = [EMPTY; 4];
+  [CORE_MAILBOXES; 4]
+};
+
+/// The simulated reload value of the local timer
+static LOCAL_TIMER_RELOAD: AtomicU32 = AtomicU32::new(0);
+/// Whether the simulated local timer is running
+static LOCAL_TIMER_RUNNING: AtomicBool = AtomicBool::new(false);
+/// The core the local timer interrupt is routed to, the bit 2 is set if it is routed as FIQ
+static LOCAL_TIMER_ROUTING: AtomicUsize = AtomicUsize::new(0);
+
+/// The simulated DMA interrupt status register
+static DMA_PENDING: AtomicU32 = AtomicU32::new(0);
+
+/// The simulated registers of the GPIO block up to the asynchronous falling edge detect enable registers
+static GPIO_REGISTERS: [AtomicU32; GPIO_REGISTER_COUNT] = {
+  // the constant is only used as the initial value of each register
+  #[allow(clippy::declare_interior_mutable_const)]
+  const ZERO: AtomicU32 = AtomicU32::new(0);
+  [ZERO; GPIO_REGISTER_COUNT]
+};
+const GPIO_REGISTER_COUNT: usize = (GPAFEN0 >> 2) + 2;
+
+/// The GPU interrupts are handled in the banks 0..2
+const GPU_BANKS: usize = 3;
+/// The core specific interrupts are handled in bank 3
+const CORE_BANK: usize = 3;
+
+/*************** simulated interrupt controller backend ***************/
+
+pub(crate) fn initialize() {
+  disable_all();
+
+  // raising IRQ only if something is written to mailbox 3 for any of the cores
+  for (core, enabled) in CORE_ENABLED[0].iter().enumerate() {
+    enabled.fetch_or(irq_bit(Interrupt::core_mailbox(core, 3)), Ordering::SeqCst);
+  }
+}
+
+pub(crate) fn initialize_core() {}
+
+pub(crate) fn activate(irq: Interrupt) {
+  modify_enabled(irq, core_of(irq), mode_of(irq), true);
+}
+
+pub(crate) fn deactivate(irq: Interrupt) {
+  modify_enabled(irq, core_of(irq), mode_of(irq), false);
+}
+
+pub(crate) fn activate_on_core(irq: Interrupt, core: usize) {
+  modify_enabled(irq, core, RoutingMode::Irq, true);
+}
+
+pub(crate) fn deactivate_on_core(irq: Interrupt, core: usize) {
+  modify_enabled(irq, core, RoutingMode::Irq, false);
+}
+
+pub(crate) fn activate_fiq(irq: Interrupt) {
+  modify_enabled(irq, core_of(irq), RoutingMode::Fiq, true);
+}
+
+pub(crate) fn deactivate_fiq(irq: Interrupt) {
+  modify_enabled(irq, core_of(irq), RoutingMode::Fiq, false);
+}
+
+pub(crate) fn route_gpu_irqs(core: usize, mode: RoutingMode) {
+  GPU_ROUTING[mode as usize].store(core, Ordering::SeqCst);
+}
+
+/// Route the local timer interrupt to the given core. The interrupt keeps it's enabled and pending state.
+pub(crate) fn route_local_timer(core: usize, mode: RoutingMode) {
+  let (current_core, current_mode) = local_timer_routing();
+  let enabled = is_enabled(Interrupt::LocalTimer, current_core, current_mode);
+  let pending =
+    CORE_PENDING[current_core].load(Ordering::SeqCst) & irq_bit(Interrupt::LocalTimer) != 0;
+  modify_enabled(Interrupt::LocalTimer, current_core, current_mode, false);
+  modify_pending(Interrupt::LocalTimer, current_core, false);
+  let fiq = if mode == RoutingMode::Fiq { 0b100 } else { 0 };
+  LOCAL_TIMER_ROUTING.store(core | fiq, Ordering::SeqCst);
+  modify_enabled(Interrupt::LocalTimer, core, mode, enabled);
+  modify_pending(Interrupt::LocalTimer, core, pending);
+}
+
+pub(crate) fn get_pending_irqs() -> [u32; 4] {
+  pending(RoutingMode::Irq)
+}
+
+pub(crate) fn get_pending_fiqs() -> [u32; 4] {
+  pending(RoutingMode::Fiq)
+}
+
+pub(crate) fn end_of_interrupt() {}
+
+#[cfg(feature = "nesting")]
+pub(crate) fn mask_gpu_irqs(mask: [u32; 3]) -> [u32; 3] {
+  let mut masked = [0; 3];
+  for (bank, masked) in masked.iter_mut().enumerate() {
+    *masked = GPU_ENABLED[RoutingMode::Irq as usize][bank].fetch_and(!mask[bank], Ordering::SeqCst)
+      & mask[bank];
+  }
+  masked
+}
+
+#[cfg(feature = "nesting")]
+pub(crate) fn unmask_gpu_irqs(masked: [u32; 3]) {
+  for (bank, &masked) in masked.iter().enumerate() {
+    GPU_ENABLED[RoutingMode::Irq as usize][bank].fetch_or(masked, Ordering::SeqCst);
+  }
+}
+
+/// Get the number of the core the interrupt dispatching is simulated on
+pub(crate) fn core_id() -> usize {
+  CURRENT_CORE.load(Ordering::SeqCst)
+}
+
+pub(crate) fn enable_irq() {
+  DAIF.fetch_and(!DAIF_IRQ, Ordering::SeqCst);
+}
+
+pub(crate) fn enable_fiq() {
+  DAIF.fetch_and(!DAIF_FIQ, Ordering::SeqCst);
+}
+
+pub(crate) fn disable_irq() {
+  DAIF.fetch_or(DAIF_IRQ, Ordering::SeqCst);
+}
+
+pub(crate) fn disable_fiq() {
+  DAIF.fetch_or(DAIF_FIQ, Ordering::SeqCst);
+}
+
+/// Read the simulated interrupt mask bits of the ``DAIF`` register
+pub(crate) fn daif() -> u64 {
+  DAIF.load(Ordering::SeqCst)
+}
+
+/// Write the simulated interrupt mask bits of the ``DAIF`` register
+pub(crate) fn set_daif(daif: u64) {
+  DAIF.store(daif, Ordering::SeqCst);
+}
+
+/// Read the simulated exception link register and saved program status register
+#[cfg(feature = "nesting")]
+pub(crate) fn exception_state() -> (u64, u64) {
+  (
+    EXCEPTION_STATE[0].load(Ordering::SeqCst),
+    EXCEPTION_STATE[1].load(Ordering::SeqCst),
+  )
+}
+
+/// Write the simulated exception link register and saved program status register
+#[cfg(feature = "nesting")]
+pub(crate) fn set_exception_state(state: (u64, u64)) {
+  EXCEPTION_STATE[0].store(state.0, Ordering::SeqCst);
+  EXCEPTION_STATE[1].store(state.1, Ordering::SeqCst);
+}
+
+/// Read the simulated physical count of the generic timer
+#[cfg_attr(not(feature = "stats"), allow(dead_code))]
+pub(crate) fn counter() -> u64 {
+  COUNTER.load(Ordering::SeqCst)
+}
+
+/// Read the simulated AUX interrupt status register
+pub(crate) fn aux_irq_status() -> u32 {
+  AUX_PENDING.load(Ordering::SeqCst)
+}
+
+/// Set the bits of the given value in the simulated mailbox of the given core. The interrupt of the mailbox is raised
+/// on the core.
+pub(crate) fn mailbox_set(core: usize, mailbox: usize, value: u32) {
+  MAILBOXES[core][mailbox].fetch_or(value, Ordering::SeqCst);
+  if value != 0 {
+    modify_pending(Interrupt::core_mailbox(core, mailbox), core, true);
+  }
+}
+
+/// Read the content of the simulated mailbox of the given core
+pub(crate) fn mailbox_read(core: usize, mailbox: usize) -> u32 {
+  MAILBOXES[core][mailbox].load(Ordering::SeqCst)
+}
+
+/// Clear the bits of the given value in the simulated mailbox of the given core. The interrupt of the mailbox is
+/// cleared once all bits are cleared.
+pub(crate) fn mailbox_clear(core: usize, mailbox: usize, value: u32) {
+  let remaining = MAILBOXES[core][mailbox].fetch_and(!value, Ordering::SeqCst) & !value;
+  if value != 0 && remaining == 0 {
+    modify_pending(Interrupt::core_mailbox(core, mailbox), core, false);
+  }
+}
+
+/// Set the reload value of the simulated local timer
+pub(crate) fn set_local_timer_reload(reload: u32) {
+  LOCAL_TIMER_RELOAD.store(reload & LOCAL_TIMER_MAX_RELOAD, Ordering::SeqCst);
+}
+
+/// Start or stop the simulated local timer
+pub(crate) fn enable_local_timer(enable: bool) {
+  LOCAL_TIMER_RUNNING.store(enable, Ordering::SeqCst);
+}
+
+/// Clear the pending local timer interrupt on the core it is routed to
+pub(crate) fn acknowledge_local_timer() {
+  modify_pending(Interrupt::LocalTimer, core_of(Interrupt::LocalTimer), false);
+}
+
+/// Read the simulated DMA interrupt status register
+pub(crate) fn dma_int_status() -> u32 {
+  DMA_PENDING.load(Ordering::SeqCst)
+}
+
+/// Read the simulated GPIO register at the given offset
+pub(crate) fn gpio_read(offset: usize) -> u32 {
+  GPIO_REGISTERS[offset >> 2].load(Ordering::SeqCst)
+}
+
+/// Write the simulated GPIO register at the given offset. Writing 1 to the bit of a pin in the event detect status
+/// registers clears the event. The interrupt line of a GPIO bank stays pending as long as an event is detected on any
+/// of it's pins.
+pub(crate) fn gpio_write(offset: usize, value: u32) {
+  if offset == GPEDS0 || offset == GPEDS0 + 4 {
+    GPIO_REGISTERS[offset >> 2].fetch_and(!value, Ordering::SeqCst);
+    update_gpio_pending();
+  } else {
+    GPIO_REGISTERS[offset >> 2].store(value, Ordering::SeqCst);
+  }
+}
+
+/*************** functions to drive the simulation ***************/
+
+/// Reset the simulated interrupt controller. All interrupts are disabled and no longer pending, the GPU interrupts and
+/// the local timer interrupt are routed to core 0, the local timer is stopped, the interrupt dispatching is simulated
+/// on core 0, IRQ's and FIQ's are masked and the generic timer count and the exception state are 0.
+pub fn reset() {
+  disable_all();
+  for pending in GPU_PENDING.iter().chain(CORE_PENDING.iter()) {
+    pending.store(0, Ordering::SeqCst);
+  }
+  AUX_PENDING.store(0, Ordering::SeqCst);
+  DMA_PENDING.store(0, Ordering::SeqCst);
+  LOCAL_TIMER_RELOAD.store(0, Ordering::SeqCst);
+  LOCAL_TIMER_RUNNING.store(false, Ordering::SeqCst);
+  LOCAL_TIMER_ROUTING.store(0, Ordering::SeqCst);
+  for mailbox in MAILBOXES.iter().flatten() {
+    mailbox.store(0, Ordering::SeqCst);
+  }
+  for register in GPIO_REGISTERS.iter() {
+    register.store(0, Ordering::SeqCst);
+  }
+  CURRENT_CORE.store(0, Ordering::SeqCst);
+  COUNTER.store(0, Ordering::SeqCst);
+  DAIF.store(DAIF_IRQ | DAIF_FIQ, Ordering::SeqCst);
+  for state in EXCEPTION_STATE.iter() {
+    state.store(0, Ordering::SeqCst);
+  }
+}
+
+/// Set the core the following interrupt dispatching is simulated on.
+///
+/// # Panics
+/// The function panics if the core is not in the range of 0..=3.
+pub fn set_core(core: usize) {
+  assert!(core < 4, "core need to be in the range of 0..=3");
+  CURRENT_CORE.store(core, Ordering::SeqCst);
+}
+
+/// Set the physical count of the generic timer to the given value.
+pub fn set_counter(count: u64) {
+  COUNTER.store(count, Ordering::SeqCst);
+}
+
+/// Advance the physical count of the generic timer by the given number of ticks.
+pub fn advance_counter(ticks: u64) {
+  COUNTER.fetch_add(ticks, Ordering::SeqCst);
+}
+
+/// Simulate taking an exception that stores the given return address and program status in the exception link
+/// register and the saved program status register.
+pub fn take_exception(return_address: u64, status: u64) {
+  EXCEPTION_STATE[0].store(return_address, Ordering::SeqCst);
+  EXCEPTION_STATE[1].store(status, Ordering::SeqCst);
+}
+
+/// Get the return address and the program status an exception return would use.
+pub fn exception_return() -> (u64, u64) {
+  (
+    EXCEPTION_STATE[0].load(Ordering::SeqCst),
+    EXCEPTION_STATE[1].load(Ordering::SeqCst),
+  )
+}
+
+/// Raise the given interrupt. The core specific interrupts are raised on the core they belong to. The core timer
+/// interrupts are raised on core 0 and the local timer interrupt on the core it is routed to.
+pub fn raise(irq: Interrupt) {
+  modify_pending(irq, core_of(irq), true);
+}
+
+/// Clear the pending state of the given interrupt.
+pub fn clear(irq: Interrupt) {
+  modify_pending(irq, core_of(irq), false);
+}
+
+/// Raise the given core specific interrupt on the given core. For GPU interrupts the core is ignored as they are raised
+/// on the core they are routed to.
+pub fn raise_on_core(irq: Interrupt, core: usize) {
+  modify_pending(irq, core, true);
+}
+
+/// Clear the pending state of the given core specific interrupt on the given core.
+pub fn clear_on_core(irq: Interrupt, core: usize) {
+  modify_pending(irq, core, false);
+}
+
+/// Read the content of the mailbox of the given core, e.g. to check whether the message of an inter-processor interrupt
+/// has been received.
+pub fn mailbox(core: usize, mailbox: usize) -> u32 {
+  mailbox_read(core, mailbox)
+}
+
+/// Raise the shared AUX interrupt for the given device. The AUX interrupt line stays pending as long as any of the
+/// devices has a pending interrupt.
+pub fn raise_aux(aux: AuxDevice) {
+  AUX_PENDING.fetch_or(aux_bit(aux), Ordering::SeqCst);
+  modify_pending(Interrupt::Aux, 0, true);
+}
+
+/// Clear the pending AUX interrupt of the given device.
+pub fn clear_aux(aux: AuxDevice) {
+  let bit = aux_bit(aux);
+  if AUX_PENDING.fetch_and(!bit, Ordering::SeqCst) & !bit == 0 {
+    modify_pending(Interrupt::Aux, 0, false);
+  }
+}
+
+/// Raise the interrupt of the given DMA channel in the range of 0..=14. The interrupt line of the channel stays pending
+/// as long as any of the channels sharing it has a pending interrupt.
+///
+/// # Panics
+/// The function panics if the DMA channel is not in the range of 0..=14
+pub fn raise_dma(channel: u8) {
+  assert!(
+    (channel as usize) < DMA_LINES.len(),
+    "DMA channel need to be in the range of 0..=14"
+  );
+  DMA_PENDING.fetch_or(1 << channel, Ordering::SeqCst);
+  modify_pending(DMA_LINES[channel as usize], 0, true);
+}
+
+/// Clear the pending interrupt of the given DMA channel like a handler acknowledging it would do.
+pub fn clear_dma(channel: u8) {
+  let irq = DMA_LINES[channel as usize];
+  // the channels sharing the interrupt line of the given channel
+  let line_channels = DMA_LINES
+    .iter()
+    .enumerate()
+    .filter(|(_, &line)| line == irq)
+    .fold(0, |channels, (channel, _)| channels | 1 << channel);
+  let pending = DMA_PENDING.fetch_and(!(1 << channel), Ordering::SeqCst) & !(1 << channel);
+  if pending & line_channels == 0 {
+    modify_pending(irq, 0, false);
+  }
+}
+
+/// Simulate the detection of an event on the given GPIO pin regardless of the event detection configured for it. The
+/// interrupt line of the bank the pin belongs to is raised as well.
+pub fn raise_gpio(pin: u8) {
+  GPIO_REGISTERS[(GPEDS0 >> 2) + (pin as usize >> 5)].fetch_or(1 << (pin & 0x1F), Ordering::SeqCst);
+  update_gpio_pending();
+}
+
+/// Clear the event detected on the given GPIO pin like a handler acknowledging it would do.
+pub fn clear_gpio(pin: u8) {
+  gpio_write(GPEDS0 + ((pin as usize >> 5) << 2), 1 << (pin & 0x1F));
+}
+
+/// Check whether an event is detected on the given GPIO pin that has not been acknowledged yet.
+pub fn is_gpio_event(pin: u8) -> bool {
+  gpio_bit(GPEDS0, pin)
+}
+
+/// Get the event detection configured for the given GPIO pin.
+pub fn gpio_edge(pin: u8) -> Option<GpioEdge> {
+  let enabled = |register| gpio_bit(register, pin);
+  match (
+    enabled(GPREN0),
+    enabled(GPFEN0),
+    enabled(GPAREN0),
+    enabled(GPAFEN0),
+  ) {
+    (true, true, _, _) => Some(GpioEdge::Both),
+    (true, false, _, _) => Some(GpioEdge::Rising),
+    (false, true, _, _) => Some(GpioEdge::Falling),
+    (_, _, true, true) => Some(GpioEdge::AsyncBoth),
+    (_, _, true, false) => Some(GpioEdge::AsyncRising),
+    (_, _, false, true) => Some(GpioEdge::AsyncFalling),
+    _ if enabled(GPHEN0) => Some(GpioEdge::High),
+    _ if enabled(GPLEN0) => Some(GpioEdge::Low),
+    _ => None,
+  }
+}
+
+/// Check whether the given interrupt is enabled to be raised as IRQ. Core specific interrupts are checked for the core
+/// they belong to or core 0.
+pub fn is_active(irq: Interrupt) -> bool {
+  is_enabled(irq, core_of(irq), RoutingMode::Irq)
+}
+
+/// Check whether the given core specific interrupt is enabled to be raised as IRQ on the given core.
+pub fn is_active_on_core(irq: Interrupt, core: usize) -> bool {
+  is_enabled(irq, core, RoutingMode::Irq)
+}
+
+/// Check whether the given interrupt is enabled to be raised as FIQ.
+pub fn is_fiq_active(irq: Interrupt) -> bool {
+  is_enabled(irq, core_of(irq), RoutingMode::Fiq)
+}
+
+/// Get the reload value of the local timer.
+pub fn local_timer_reload() -> u32 {
+  LOCAL_TIMER_RELOAD.load(Ordering::SeqCst)
+}
+
+/// Check whether the local timer is running.
+pub fn is_local_timer_running() -> bool {
+  LOCAL_TIMER_RUNNING.load(Ordering::SeqCst)
+}
+
+/// Get the core and the [RoutingMode] the local timer interrupt is routed to.
+pub fn local_timer_routing() -> (usize, RoutingMode) {
+  let routing = LOCAL_TIMER_ROUTING.load(Ordering::SeqCst);
+  let mode = if routing & 0b100 != 0 {
+    RoutingMode::Fiq
+  } else {
+    RoutingMode::Irq
+  };
+  (routing & 0b11, mode)
+}
+
+/// Get the core the GPU interrupts raised with the given [RoutingMode] are routed to.
+pub fn gpu_routing(mode: RoutingMode) -> usize {
+  GPU_ROUTING[mode as usize].load(Ordering::SeqCst)
+}
+
+/*************** internal model ***************/
+
+/// Disable all interrupts and route the GPU interrupts to core 0
+fn disable_all() {
+  let gpu_enabled = GPU_ENABLED.iter().flatten();
+  let core_enabled = CORE_ENABLED.iter().flatten();
+  for enabled in gpu_enabled.chain(core_enabled) {
+    enabled.store(0, Ordering::SeqCst);
+  }
+  for routing in GPU_ROUTING.iter() {
+    routing.store(0, Ordering::SeqCst);
+  }
+}
+
+/// Get the enabled and pending interrupts of the current core
+fn pending(mode: RoutingMode) -> [u32; 4] {
+  let core = core_id();
+  let mut pendings = [0; 4];
+  if GPU_ROUTING[mode as usize].load(Ordering::SeqCst) == core {
+    for (bank, pending) in pendings.iter_mut().take(GPU_BANKS).enumerate() {
+      *pending = GPU_PENDING[bank].load(Ordering::SeqCst)
+        & GPU_ENABLED[mode as usize][bank].load(Ordering::SeqCst);
+    }
+  }
+  pendings[CORE_BANK] = CORE_PENDING[core].load(Ordering::SeqCst)
+    & CORE_ENABLED[mode as usize][core].load(Ordering::SeqCst);
+  // like the core specific pending register the GPU bit signals the GPU interrupts pending on this core
+  if pendings.iter().take(GPU_BANKS).any(|&pending| pending != 0) {
+    pendings[CORE_BANK] |= irq_bit(Interrupt::CoreGPU);
+  }
+
+  pendings
+}
+
+fn modify_enabled(irq: Interrupt, core: usize, mode: RoutingMode, enable: bool) {
+  let enabled = match irq_bank(irq) {
+    CORE_BANK => &CORE_ENABLED[mode as usize][core],
+    bank => &GPU_ENABLED[mode as usize][bank],
+  };
+  modify(enabled, irq_bit(irq), enable);
+}
+
+fn modify_pending(irq: Interrupt, core: usize, raise: bool) {
+  let pending = match irq_bank(irq) {
+    CORE_BANK => &CORE_PENDING[core],
+    bank => &GPU_PENDING[bank],
+  };
+  modify(pending, irq_bit(irq), raise);
+}
+
+fn is_enabled(irq: Interrupt, core: usize, mode: RoutingMode) -> bool {
+  let enabled = match irq_bank(irq) {
+    CORE_BANK => &CORE_ENABLED[mode as usize][core],
+    bank => &GPU_ENABLED[mode as usize][bank],
+  };
+  enabled.load(Ordering::SeqCst) & irq_bit(irq) != 0
+}
+
+fn modify(register: &AtomicU32, bit: u32, set: bool) {
+  if set {
+    register.fetch_or(bit, Ordering::SeqCst);
+  } else {
+    register.fetch_and(!bit, Ordering::SeqCst);
+  }
+}
+
+/// The core a core specific interrupt belongs to if no core is given explicitly
+fn core_of(irq: Interrupt) -> usize {
+  match irq {
+    Interrupt::LocalTimer => local_timer_routing().0,
+    _ => irq.mailbox().map_or(0, |(core, _)| core),
+  }
+}
+
+/// The exception kind an interrupt activated with ``activate`` is raised with. Only the local timer interrupt could be
+/// routed as FIQ this way.
+fn mode_of(irq: Interrupt) -> RoutingMode {
+  match irq {
+    Interrupt::LocalTimer => local_timer_routing().1,
+    _ => RoutingMode::Irq,
+  }
+}
+
+fn irq_bank(irq: Interrupt) -> usize {
+  (irq as usize) >> 5
+}
+
+fn irq_bit(irq: Interrupt) -> u32 {
+  1 << ((irq as u32) & 0x1F)
+}
+
+/// Check whether the bit of the given GPIO pin is set in the register pair starting at the given offset
+fn gpio_bit(offset: usize, pin: u8) -> bool {
+  GPIO_REGISTERS[(offset >> 2) + (pin as usize >> 5)].load(Ordering::SeqCst) & 1 << (pin & 0x1F)
+    != 0
+}
+
+/// Raise the interrupt line of each GPIO bank with a detected event on any of it's pins and clear the others
+fn update_gpio_pending() {
+  let events = gpio_read(GPEDS0) as u64 | (gpio_read(GPEDS0 + 4) as u64) << 32;
+  let banks = [
+    Interrupt::GpioBank0,
+    Interrupt::GpioBank1,
+    Interrupt::GpioBank2,
+  ];
+  for (&bank, &pins) in banks.iter().zip(GPIO_BANK_PINS.iter()) {
+    modify_pending(bank, 0, events & pins != 0);
+  }
+}
+
+/// The bit of the device in the AUX interrupt status register
+fn aux_bit(aux: AuxDevice) -> u32 {
+  match aux {
+    AuxDevice::Uart1 => 1 << 0,
+    AuxDevice::Spi1 => 1 << 1,
+    AuxDevice::Spi2 => 1 << 2,
+  }
+}
@@ -0,0 +1,131 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # Inter-processor interrupts
+//!
+//! Each core has 4 mailboxes that raise an interrupt on this core as long as any bit is set in them. The mailbox 3 is
+//! used to send inter-processor interrupts (IPI's) with [send_ipi]. The mailboxes 0..2 could be used for further
+//! classes of IPI's, e.g. to request a reschedule or a TLB shootdown, with [send_mailbox_ipi]. The interrupt is raised
+//! as ``Core<n>Mailbox<m>`` on the target core. Before the handler is called the mailbox is read and cleared and the
+//! message is sent to the channel registered for the interrupt if it is an [IsrSender<u32>](crate::IsrSender). The
+//! mailbox interrupts could not be activated with a static channel as it's size is not known when the message is
+//! received. The bit 31 of the mailbox 3 is reserved for the remote function calls.
+//!
+//! ```no_run
+//! # use ruspiro_interrupt::*;
+//! // core 1 receives the messages sent to it's mailbox
+//! let (tx, rx) = isr_channel::<u32>();
+//! activate(Interrupt::Core1Mailbox3, tx);
+//!
+//! // any core sends a message to core 1
+//! send_ipi(1, 0x1);
+//! ```
+//!
+
+use crate::{
+  interface,
+  isrcell::IsrCell,
+  remotecall::{self, REMOTE_CALL},
+  ErasedSender, Interrupt, IsrChannel, IsrSender, CORE_BANK,
+};
+
+/// The mailbox used to send inter-processor interrupts with [send_ipi]
+const IPI_MAILBOX: usize = 3;
+
+/// Send an inter-processor interrupt with the given message to the given core. The bits of the message are set in the
+/// mailbox 3 of the core that raises the ``Core<n>Mailbox3`` interrupt on it as long as any bit is set. Messages sent
+/// to a core before it handled the interrupt are combined. A message of 0 does not raise an interrupt. The bit 31 is
+/// reserved for the remote function calls of [call_on_core](crate::call_on_core) and can not be sent with this
+/// function.
+///
+/// # Panics
+/// The function panics if the core is not in the range of 0..=3 or if the reserved bit 31 of the message is set.
+pub fn send_ipi(core: usize, message: u32) {
+  send_mailbox_ipi(core, IPI_MAILBOX, message);
+}
+
+/// Send an inter-processor interrupt with the given message to the given mailbox of the given core. The mailbox raises
+/// the ``Core<n>Mailbox<m>`` interrupt on the core as long as any bit is set. Other than the mailbox 3 the interrupts of
+/// the mailboxes 0..2 need to be activated before they are raised. The bit 31 of the mailbox 3 is reserved for the
+/// remote function calls.
+///
+/// # Panics
+/// The function panics if the core or the mailbox is not in the range of 0..=3 or if the reserved bit 31 of the
+/// message is set for the mailbox 3.
+pub fn send_mailbox_ipi(core: usize, mailbox: usize, message: u32) {
+  assert!(
+    mailbox != IPI_MAILBOX || message & REMOTE_CALL == 0,
+    "the bit 31 of the mailbox 3 is reserved for the remote function calls"
+  );
+  set_mailbox(core, mailbox, message);
+}
+
+/// Notify the given core about the remote calls queued for it with the reserved bit of the mailbox 3
+pub(crate) fn notify_remote_call(core: usize) {
+  set_mailbox(core, IPI_MAILBOX, REMOTE_CALL);
+}
+
+/// Set the bits of the message in the given mailbox of the given core
+fn set_mailbox(core: usize, mailbox: usize, message: u32) {
+  assert!(core < 4, "core need to be in the range of 0..=3");
+  assert!(mailbox < 4, "mailbox need to be in the range of 0..=3");
+  // ensure the data written before sending the message is visible to the receiving core
+  #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
+  unsafe {
+    asm!("dmb sy")
+  };
+  interface::mailbox_set(core, mailbox, message);
+}
+
+/// Get the core and the mailbox of the inter-processor interrupt at the given bank and bit position if it is one
+pub(crate) fn ipi_mailbox(bank: usize, num: usize) -> Option<(usize, usize)> {
+  if bank != CORE_BANK {
+    return None;
+  }
+  Interrupt::try_from(((bank << 5) + num) as u8)
+    .ok()
+    .and_then(Interrupt::mailbox)
+}
+
+/// Ensure a mailbox interrupt is not activated with a static channel. The message is only passed to an [IsrSender<u32>],
+/// the static channel would silently miss it.
+///
+/// # Panics
+/// The function panics if the interrupt is a mailbox interrupt and the channel is a static one
+pub(crate) fn check_channel(irq: Interrupt, channel: &IsrChannel) {
+  assert!(
+    irq.mailbox().is_none() || !matches!(channel, Some(ErasedSender::Static(_))),
+    "mailbox interrupts need to be activated with an 'IsrSender<u32>' to receive the messages"
+  );
+}
+
+/// Read and clear the given mailbox of the given core and send the message to the given channel if it is an
+/// [IsrSender<u32>]
+pub(crate) fn receive(core: usize, mailbox: usize, channel: &IsrCell<ErasedSender>) {
+  let message = interface::mailbox_read(core, mailbox);
+  if message == 0 {
+    return;
+  }
+  interface::mailbox_clear(core, mailbox, message);
+  // the remote calls queued for the core are notified with a reserved bit of the mailbox 3
+  let message = if mailbox == IPI_MAILBOX && message & REMOTE_CALL != 0 {
+    remotecall::execute(core);
+    message & !REMOTE_CALL
+  } else {
+    message
+  };
+  if message == 0 {
+    return;
+  }
+  channel.lend(|channel| {
+    if let Some(ErasedSender::Boxed(sender)) = channel {
+      if let Some(sender) = sender.downcast_ref::<IsrSender<u32>>() {
+        sender.send(message);
+      }
+    }
+  });
+}
@@ -16,7 +16,7 @@
 /// individual handler implementation.
 ///
 #[repr(u8)]
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum Interrupt {
   // IRQ's appearing in the GPU pending registers
   // IRQ 0 - 31 (only the IRQ's that could be registered)
@@ -28,7 +28,6 @@ pub enum Interrupt {
   CoreSync1 = 13,
   CoreSync2 = 14,
   CoreSync3 = 15,
-  /*
   DMA0 = 16,
   DMA1 = 17,
   DMA2 = 18,
@@ -42,7 +41,6 @@ pub enum Interrupt {
   DMA12 = 26,
   DMA13 = 27,
   DMA14 = 28,
-  */
   Aux = 29,
   // ARM
   Arm = 30,
@@ -50,24 +48,23 @@ pub enum Interrupt {
   GpuDma = 31,
 
   // IRQ 32 - 63
-  /*HostPort        = 32, // HDMI CEC
-  VideoScaler     = 33, // HVS
-  Ccp2Tx          = 34, // RPIVID
-  Sdc             = 35,
-  Dsi0            = 36,
-  Ave             = 37, // Pixel Valve2
-  Cam0            = 38,
-  Cam1            = 39,
-  Hdmi0           = 40,
-  Hdmi1           = 41,
-  PixelValve3     = 42,
-  I2cSpi          = 43,
-  Dsi1            = 44,
-  Pwa0            = 45, // PixelValve0
-  Pwa1            = 46, // PixelValve 1 & 4
-  Cpr             = 47,
-  Smi             = 48,
-  */
+  HostPort = 32,    // HDMI CEC
+  VideoScaler = 33, // HVS
+  Ccp2Tx = 34,      // RPIVID
+  Sdc = 35,
+  Dsi0 = 36,
+  Ave = 37, // Pixel Valve2
+  Cam0 = 38,
+  Cam1 = 39,
+  Hdmi0 = 40,
+  Hdmi1 = 41,
+  PixelValve3 = 42,
+  I2cSpi = 43,
+  Dsi1 = 44,
+  Pwa0 = 45, // PixelValve0
+  Pwa1 = 46, // PixelValve 1 & 4
+  Cpr = 47,
+  Smi = 48,
   GpioBank0 = 49, // GPIO Bank 0
   GpioBank1 = 50,
   GpioBank2 = 51, // Not existend at BCM2837???
@@ -104,4 +101,141 @@ pub enum Interrupt {
   //CorePMU = 105,
   //CoreAxi = 106,
   LocalTimer = 107,
+  // the mailbox 0..2 interrupts of each core
+  Core0Mailbox0 = 108,
+  Core1Mailbox0 = 109,
+  Core2Mailbox0 = 110,
+  Core3Mailbox0 = 111,
+  Core0Mailbox1 = 112,
+  Core1Mailbox1 = 113,
+  Core2Mailbox1 = 114,
+  Core3Mailbox1 = 115,
+  Core0Mailbox2 = 116,
+  Core1Mailbox2 = 117,
+  Core2Mailbox2 = 118,
+  Core3Mailbox2 = 119,
+}
+
+impl Interrupt {
+  /// Get the interrupt raised by the given mailbox 0..3 of the given core 0..3
+  pub(crate) fn core_mailbox(core: usize, mailbox: usize) -> Interrupt {
+    let num = match mailbox {
+      3 => 100 + core,
+      _ => 108 + (mailbox << 2) + core,
+    };
+    // the numbers are within the mailbox interrupts for all valid cores and mailboxes
+    Interrupt::try_from(num as u8).unwrap()
+  }
+
+  /// Get the core and the mailbox of a mailbox interrupt
+  pub(crate) fn mailbox(self) -> Option<(usize, usize)> {
+    match self as usize {
+      num @ 100..=103 => Some((num - 100, 3)),
+      num @ 108..=119 => Some(((num - 108) & 0x3, (num - 108) >> 2)),
+      _ => None,
+    }
+  }
+}
+
+/// Get the interrupt of the given number. The number is the bank of the interrupt multiplied by 32 plus it's bit position
+/// within the bank. Numbers without an interrupt a handler could be implemented for are returned as error.
+impl TryFrom<u8> for Interrupt {
+  type Error = u8;
+
+  fn try_from(value: u8) -> Result<Self, Self::Error> {
+    match value {
+      1 => Ok(Interrupt::SystemTimer1),
+      3 => Ok(Interrupt::SystemTimer3),
+      8 => Ok(Interrupt::Isp),
+      9 => Ok(Interrupt::Usb),
+      12 => Ok(Interrupt::CoreSync0),
+      13 => Ok(Interrupt::CoreSync1),
+      14 => Ok(Interrupt::CoreSync2),
+      15 => Ok(Interrupt::CoreSync3),
+      16 => Ok(Interrupt::DMA0),
+      17 => Ok(Interrupt::DMA1),
+      18 => Ok(Interrupt::DMA2),
+      19 => Ok(Interrupt::DMA3),
+      20 => Ok(Interrupt::DMA4),
+      21 => Ok(Interrupt::DMA5),
+      22 => Ok(Interrupt::DMA6),
+      23 => Ok(Interrupt::DMA7_8),
+      24 => Ok(Interrupt::DMA9_10),
+      25 => Ok(Interrupt::DMA11),
+      26 => Ok(Interrupt::DMA12),
+      27 => Ok(Interrupt::DMA13),
+      28 => Ok(Interrupt::DMA14),
+      29 => Ok(Interrupt::Aux),
+      30 => Ok(Interrupt::Arm),
+      31 => Ok(Interrupt::GpuDma),
+      32 => Ok(Interrupt::HostPort),
+      33 => Ok(Interrupt::VideoScaler),
+      34 => Ok(Interrupt::Ccp2Tx),
+      35 => Ok(Interrupt::Sdc),
+      36 => Ok(Interrupt::Dsi0),
+      37 => Ok(Interrupt::Ave),
+      38 => Ok(Interrupt::Cam0),
+      39 => Ok(Interrupt::Cam1),
+      40 => Ok(Interrupt::Hdmi0),
+      41 => Ok(Interrupt::Hdmi1),
+      42 => Ok(Interrupt::PixelValve3),
+      43 => Ok(Interrupt::I2cSpi),
+      44 => Ok(Interrupt::Dsi1),
+      45 => Ok(Interrupt::Pwa0),
+      46 => Ok(Interrupt::Pwa1),
+      47 => Ok(Interrupt::Cpr),
+      48 => Ok(Interrupt::Smi),
+      49 => Ok(Interrupt::GpioBank0),
+      50 => Ok(Interrupt::GpioBank1),
+      51 => Ok(Interrupt::GpioBank2),
+      52 => Ok(Interrupt::GpioBank3),
+      53 => Ok(Interrupt::I2c),
+      54 => Ok(Interrupt::Spi),
+      55 => Ok(Interrupt::I2sPcm),
+      56 => Ok(Interrupt::Sdio),
+      57 => Ok(Interrupt::Pl011),
+      64 => Ok(Interrupt::ArmTimer),
+      65 => Ok(Interrupt::ArmMailbox),
+      66 => Ok(Interrupt::ArmDoorbell0),
+      67 => Ok(Interrupt::ArmDoorbell1),
+      68 => Ok(Interrupt::ArmGpu0Halted),
+      69 => Ok(Interrupt::ArmGpu1Halted),
+      70 => Ok(Interrupt::ArmIllegalType1),
+      71 => Ok(Interrupt::ArmIllegalType0),
+      72 => Ok(Interrupt::ArmPending1),
+      73 => Ok(Interrupt::ArmPending2),
+      96 => Ok(Interrupt::CntPsIrq),
+      97 => Ok(Interrupt::CntPnsIrq),
+      98 => Ok(Interrupt::CntHpIrq),
+      99 => Ok(Interrupt::CntVIrq),
+      100 => Ok(Interrupt::Core0Mailbox3),
+      101 => Ok(Interrupt::Core1Mailbox3),
+      102 => Ok(Interrupt::Core2Mailbox3),
+      103 => Ok(Interrupt::Core3Mailbox3),
+      104 => Ok(Interrupt::CoreGPU),
+      107 => Ok(Interrupt::LocalTimer),
+      108 => Ok(Interrupt::Core0Mailbox0),
+      109 => Ok(Interrupt::Core1Mailbox0),
+      110 => Ok(Interrupt::Core2Mailbox0),
+      111 => Ok(Interrupt::Core3Mailbox0),
+      112 => Ok(Interrupt::Core0Mailbox1),
+      113 => Ok(Interrupt::Core1Mailbox1),
+      114 => Ok(Interrupt::Core2Mailbox1),
+      115 => Ok(Interrupt::Core3Mailbox1),
+      116 => Ok(Interrupt::Core0Mailbox2),
+      117 => Ok(Interrupt::Core1Mailbox2),
+      118 => Ok(Interrupt::Core2Mailbox2),
+      119 => Ok(Interrupt::Core3Mailbox2),
+      _ => Err(value),
+    }
+  }
+}
+
+/// The kind of exception the interrupts are signaled with to the core they are routed to
+#[derive(Copy, Clone, PartialEq)]
+pub enum RoutingMode {
+  /// Signal the interrupts as IRQ
+  Irq,
+  /// Signal the interrupts as FIQ
+  Fiq,
 }
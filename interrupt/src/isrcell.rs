@@ -0,0 +1,216 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # Interrupt safe storage
+//!
+//! The channels and the handlers of the interrupts are registered from the normal processing on any core while they are
+//! used by the interrupt handling on the core the interrupt is raised on. The [IsrCell] keeps such a value and guards
+//! the access with a spinlock. While the lock is held the interrupts are masked on the current core, so an interrupt
+//! could never try to take the lock the interrupted code is already holding.
+//!
+//! The lock is only held to put a value into the cell or to lend it to the interrupt handling. While the handler is
+//! running the value stays in the cell but the lock is released, so the handler itself is free to activate or
+//! deactivate any interrupt and the same interrupt could be dispatched on another core at the same time. The channels
+//! are lent for shared access to any number of handlers. The runtime handlers are lent for exclusive access to one core
+//! at a time. Another core waits until they are returned, while an interrupt on the same core does not get them.
+//!
+//! A value replaced while it is lent stays in the cell until it is returned. It is then kept in the cell until the next
+//! replacement outside of a lend hands it out for dropping, so a value is never dropped within the interrupt handling.
+//! As the lock is an atomic the MMU need to be configured before the first interrupt is activated.
+//!
+
+use core::{
+  cell::UnsafeCell,
+  sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::{interface, InterruptGuard};
+
+/// An optional value that could be replaced from any core and lent to the interrupt handling
+pub(crate) struct IsrCell<T> {
+  locked: AtomicBool,
+  /// The state of the lends of the value. It is only accessed while the lock is held
+  lends: UnsafeCell<Lends<T>>,
+  /// The value is only changed while the lock is held and the value is not lent
+  value: UnsafeCell<Option<T>>,
+}
+
+/// The lends of the value of an [IsrCell] and the values given to the cell while it has been lent
+struct Lends<T> {
+  /// The number of functions the value is lent to for shared access
+  shared: usize,
+  /// The core the value is lent to for exclusive access
+  exclusive: Option<usize>,
+  /// The value given to the cell while it has been lent. It replaces the lent value once it is returned
+  next: Option<Option<T>>,
+  /// The lent value that has been replaced once it was returned
+  retired: Option<T>,
+}
+
+// the value is only accessed while the lock is held or while it is lent
+unsafe impl<T> Sync for IsrCell<T> {}
+
+impl<T> IsrCell<T> {
+  // the constant is only used as the initial value of the cells within the lists of the interrupts
+  #[allow(clippy::declare_interior_mutable_const)]
+  pub(crate) const EMPTY: Self = Self::new();
+
+  /// Create an empty cell
+  pub(crate) const fn new() -> Self {
+    Self {
+      locked: AtomicBool::new(false),
+      lends: UnsafeCell::new(Lends {
+        shared: 0,
+        exclusive: None,
+        next: None,
+        retired: None,
+      }),
+      value: UnsafeCell::new(None),
+    }
+  }
+
+  /// Replace the value of the cell and return the previous one. If the value is lent it is replaced once it is
+  /// returned. In this case ``None`` is returned as the lent value could only be handed out with the next replacement.
+  pub(crate) fn replace(&self, value: Option<T>) -> Option<T> {
+    let (previous, retired) = self.lock(|lends| {
+      if lends.is_lent() {
+        // a value given while the value is lent as well is handed back
+        (lends.next.replace(value).flatten(), None)
+      } else {
+        let current = unsafe { &mut *self.value.get() };
+        (core::mem::replace(current, value), lends.retired.take())
+      }
+    });
+    // the value replaced once it was returned is dropped without holding the lock
+    drop(retired);
+    previous
+  }
+
+  /// Take the value out of the cell and leave it empty
+  pub(crate) fn take(&self) -> Option<T> {
+    self.replace(None)
+  }
+
+  /// Modify the value of the cell in place with the given function. The lock is held while the function is running, so
+  /// it need to be short and must not access the cell itself. The value of the cell must not be lent.
+  pub(crate) fn modify<F, R>(&self, f: F) -> R
+  where
+    F: FnOnce(&mut Option<T>) -> R,
+  {
+    self.lock(|lends| {
+      assert!(!lends.is_lent(), "the value of the cell is lent");
+      f(unsafe { &mut *self.value.get() })
+    })
+  }
+
+  /// Lend the value of the cell to the given function for shared access. The lock is not held while the function is
+  /// running. The value could be lent to functions on other cores at the same time, but not while it is lent for
+  /// exclusive access. If it is lent for exclusive access on the current core the function does not get it.
+  pub(crate) fn lend<F, R>(&self, f: F) -> R
+  where
+    F: FnOnce(&Option<T>) -> R,
+  {
+    let core = interface::core_id();
+    loop {
+      let lent = self.lock(|lends| match lends.exclusive {
+        Some(lender) if lender == core => Some(false),
+        Some(_) => None,
+        None => {
+          lends.shared += 1;
+          Some(true)
+        }
+      });
+      match lent {
+        Some(true) => break,
+        Some(false) => return f(&None),
+        None => core::hint::spin_loop(),
+      }
+    }
+    // the value is not changed while it is lent
+    let result = f(unsafe { &*self.value.get() });
+    self.give_back(|lends| lends.shared -= 1);
+    result
+  }
+
+  /// Lend the value of the cell to the given function for exclusive access. The lock is not held while the function is
+  /// running. While the value is lent to another core the current core waits for it to be returned. If it is lent on
+  /// the current core already the function does not get it.
+  pub(crate) fn lend_mut<F, R>(&self, f: F) -> R
+  where
+    F: FnOnce(&mut Option<T>) -> R,
+  {
+    let core = interface::core_id();
+    loop {
+      let lent = self.lock(|lends| match lends.exclusive {
+        Some(lender) if lender == core => Some(false),
+        None if lends.shared == 0 => {
+          lends.exclusive = Some(core);
+          Some(true)
+        }
+        _ => None,
+      });
+      match lent {
+        Some(true) => break,
+        Some(false) => return f(&mut None),
+        None => core::hint::spin_loop(),
+      }
+    }
+    // the value is only accessed by this function while it is lent
+    let result = f(unsafe { &mut *self.value.get() });
+    self.give_back(|lends| lends.exclusive = None);
+    result
+  }
+
+  /// Return the lent value to the cell. Once it is no longer lent a value given to the cell meanwhile replaces it and
+  /// the lent value is kept until the next replacement. Only if the value replaced with a previous lend is still kept it
+  /// is dropped.
+  fn give_back<F>(&self, f: F)
+  where
+    F: FnOnce(&mut Lends<T>),
+  {
+    let dropped = self.lock(|lends| {
+      f(lends);
+      match lends.next.take() {
+        Some(next) if !lends.is_lent() => {
+          let current = unsafe { &mut *self.value.get() };
+          let replaced = core::mem::replace(current, next);
+          replaced.and_then(|replaced| lends.retired.replace(replaced))
+        }
+        next => {
+          lends.next = next;
+          None
+        }
+      }
+    });
+    drop(dropped);
+  }
+
+  /// Execute the given function with the lock held and the interrupts masked on the current core. The function may
+  /// only access the value of the cell if it is not lent.
+  fn lock<F, R>(&self, f: F) -> R
+  where
+    F: FnOnce(&mut Lends<T>) -> R,
+  {
+    let _guard = InterruptGuard::new();
+    while self
+      .locked
+      .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+      .is_err()
+    {
+      core::hint::spin_loop();
+    }
+    let result = f(unsafe { &mut *self.lends.get() });
+    self.locked.store(false, Ordering::Release);
+    result
+  }
+}
+
+impl<T> Lends<T> {
+  fn is_lent(&self) -> bool {
+    self.shared > 0 || self.exclusive.is_some()
+  }
+}
@@ -21,7 +21,7 @@
 //! use ruspiro_interrupt::{self as irq, IrqHandler, IsrSender, isr_channel};
 //!
 //! #[IrqHandler(ArmTimer)]
-//! fn timer_handler(channel: Option<IsrSender<Box<dyn Any>>>) {
+//! fn timer_handler(channel: Option<IsrSender<()>>) {
 //!     // IMPORTANT: acknowledge the irq !
 //!
 //!     // implement stuff that shall be executed if the interrupt is raised...
@@ -38,15 +38,20 @@
 //! use ruspiro_interrupt::*;
 //!
 //! #[IrqHandler(Aux, Uart1)]
-//! fn aux_uart1_handler(channel: Option<IsrSender<Box<dyn Any>>>) {
+//! fn aux_uart1_handler(channel: Option<IsrSender<u8>>) {
 //!     // implement Uart1 interrupt handler here
 //! }
 //! ```
 //!
+//! The handler receives the sender of the channel the interrupt has been activated with. The type of the data send
+//! through the channel is given by the handler parameter and need to match the type of the channel used with the
+//! activation. Otherwise the handler receives ``None``. If the handler does not use a channel the parameter can be
+//! omitted.
+//!
 //! With the actual interrupt handling routines in place the corresponding interrupts need to be configured and
 //! activated like the following.
 //!
-//! ```no_run
+//! ```ignore
 //! fn main() {
 //!     // as we have an interrupt handler defined we need to enable interrupt handling globally as well
 //!     // as the specific interrupt we have a handler implemented for
@@ -56,7 +61,7 @@
 //!     let (timer_tx, mut timer_rx) = isr_channel::<()>();
 //!     irq::activate(Interrupt::ArmTimer, timer_tx);
 //!     // activate an irq that does not use a channel as all processing is done inside it's handler
-//!     irq::activate(Interrupt::Aux, None);
+//!     irq::activate(Interrupt::Pl011, None);
 //!
 //!     enable_interrupts();
 //!
@@ -74,27 +79,95 @@
 //! ## Limitations for shared interrupt lines
 //!
 //! However, only a limited ammount of shared interrupt lines implementation is available with the current version -
-//! which is only the **Aux** interrupt at the moment.
+//! which is the **Aux** interrupt, the DMA interrupt lines **DMA7_8** and **DMA9_10** and the GPIO interrupt lines
+//! **GpioBank0**, **GpioBank1** and **GpioBank2**. The handler of a single GPIO pin is implemented like this:
+//!
+//! ```no_run
+//! extern crate ruspiro_interrupt; // <- this kind of usage is VERY IMPORTANT to ensure linking works as expected!
+//! use ruspiro_interrupt::*;
+//!
+//! #[IrqHandler(GpioBank0, Pin17)]
+//! fn button_handler() {
+//!     // the event has already been acknowledged
+//! }
+//!
+//! fn main() {
+//!     activate_gpio_pin(17, GpioEdge::Falling, None);
+//! }
+//! ```
 //!
 //!
 
 #[cfg(not(any(feature = "pi3", feature = "pi4_low", feature = "pi4_high")))]
 compile_error!("Either feature \"pi3\", \"pi4_low\" or \"pi4_high\" must be enabled for this crate");
 
+#[cfg(all(feature = "pi4_gic", not(any(feature = "pi4_low", feature = "pi4_high"))))]
+compile_error!("The feature \"pi4_gic\" requires the feature \"pi4_low\" or \"pi4_high\" to be enabled as well");
+
+#[cfg(all(feature = "nesting", feature = "pi4_gic"))]
+compile_error!("The feature \"nesting\" can not be used with \"pi4_gic\" as the GIC-400 has hardware priorities");
+
+#[cfg(all(feature = "sim", feature = "pi4_gic"))]
+compile_error!("The feature \"sim\" simulates the legacy interrupt controller and can not be used with \"pi4_gic\"");
+
 extern crate alloc;
 extern crate paste;
 
 mod auxhandler;
 mod bitset;
+mod critical;
+mod dmahandler;
+mod gpiohandler;
 mod interface;
+mod ipi;
 mod irqtypes;
+mod isrcell;
+mod localtimer;
+#[cfg(feature = "nesting")]
+mod nesting;
+mod remotecall;
+mod sharedline;
+#[cfg(feature = "no_alloc")]
+mod staticchannel;
+#[cfg(feature = "stats")]
+mod stats;
+mod storm;
 
 use alloc::boxed::Box;
-use auxhandler::set_aux_isrsender;
 pub use auxhandler::AuxDevice;
-use core::{any::Any, cell::RefCell};
-pub use irqtypes::Interrupt;
-pub use ruspiro_interrupt_macros::IrqHandler;
+pub use gpiohandler::GpioEdge;
+pub use critical::{
+  are_interrupts_enabled, critical_section, is_fiq_enabled, is_irq_enabled, CriticalSection,
+  InterruptGuard,
+};
+use core::{
+  any::Any,
+  convert::{Infallible, TryFrom},
+  sync::atomic::{AtomicUsize, Ordering},
+};
+pub use ipi::{send_ipi, send_mailbox_ipi};
+pub use irqtypes::{Interrupt, RoutingMode};
+use isrcell::IsrCell;
+pub use localtimer::{
+  route_local_timer, set_local_timer_reload, start_local_timer, stop_local_timer,
+};
+use sharedline::SharedLine;
+#[cfg(feature = "sim")]
+pub use interface::sim;
+#[cfg(feature = "nesting")]
+pub use nesting::{priority, set_priority};
+pub use remotecall::{call_on_all_cores, call_on_core, RemoteCallTimeout};
+pub use ruspiro_interrupt_macros::{FiqHandler, IrqHandler};
+#[cfg(feature = "no_alloc")]
+pub use staticchannel::{
+  static_isr_channel, OverflowPolicy, StaticIsrChannel, StaticIsrReceiver, StaticIsrSender,
+};
+#[cfg(feature = "stats")]
+pub use stats::{stats, stats_iter, IrqStats};
+pub use storm::{
+  clear_storm_handler, configure_storm_detection, disable_storm_detection, is_quarantined,
+  set_storm_handler,
+};
 
 #[cfg(feature = "async")]
 pub use ruspiro_channel::mpmc::async_channel as isr_channel;
@@ -109,14 +182,78 @@ pub use ruspiro_channel::mpmc::Receiver as IsrReceiver;
 #[cfg(not(feature = "async"))]
 pub use ruspiro_channel::mpmc::Sender as IsrSender;
 
-type IsrChannel = Option<IsrSender<Box<dyn Any>>>;
+/// The type erased sender of the interrupt service routine channel an interrupt has been activated with. The handler
+/// of the interrupt receives the sender with the type it has been created with.
+pub type IsrChannel = Option<ErasedSender>;
+
+/// A type erased sender of an interrupt service routine channel. The sender of a channel living on the heap is boxed,
+/// while the sender of a static channel only keeps the reference to the channel and does not allocate.
+#[doc(hidden)]
+pub enum ErasedSender {
+  Boxed(Box<dyn Any>),
+  Static(&'static dyn Any),
+}
+/// A handler registered at runtime with [register_handler]
+type IsrHandler = Box<dyn FnMut() + Send>;
+
+/// Conversion of the sender of an interrupt service routine channel into the type erased [IsrChannel] that is kept for
+/// an activated interrupt. This is implemented for the [IsrSender] of any payload type. Passing ``None`` activates an
+/// interrupt without a channel.
+pub trait IntoIsrChannel {
+  fn into_isr_channel(self) -> IsrChannel;
+}
+
+/// Conversion of the type erased [IsrChannel] back into the typed sender the handler of the interrupt receives. This
+/// is the counterpart of [IntoIsrChannel] and need to be implemented for the same senders.
+pub trait FromIsrChannel: Sized {
+  fn from_isr_channel(channel: &ErasedSender) -> Option<Self>;
+}
+
+impl<T: 'static> IntoIsrChannel for IsrSender<T> {
+  fn into_isr_channel(self) -> IsrChannel {
+    Some(ErasedSender::Boxed(Box::new(self)))
+  }
+}
+
+impl<T: 'static> FromIsrChannel for IsrSender<T> {
+  fn from_isr_channel(channel: &ErasedSender) -> Option<Self> {
+    match channel {
+      ErasedSender::Boxed(sender) => sender.downcast_ref::<Self>().cloned(),
+      ErasedSender::Static(_) => None,
+    }
+  }
+}
+
+/// ``None`` can not be constructed for any other type than the ``Option`` implementing this trait, so the compiler is
+/// able to infer it when activating an interrupt with ``None``.
+impl IntoIsrChannel for Option<Infallible> {
+  fn into_isr_channel(self) -> IsrChannel {
+    None
+  }
+}
+
+/// Get the typed sender of the interrupt service routine channel from the type erased [IsrChannel]. This is used by
+/// the handler implemented with the ``IrqHandler`` or ``FiqHandler`` attribute to provide the sender with the type of
+/// the handler parameter. If the interrupt has been activated with a sender of a different type ``None`` is returned.
+#[doc(hidden)]
+pub fn isr_sender<S: FromIsrChannel>(channel: &IsrChannel) -> Option<S> {
+  channel.as_ref().and_then(S::from_isr_channel)
+}
 
 /// One time interrupt manager initialization. This performs the initial configuration and deactivates all IRQs
 pub fn initialize() {
   interface::initialize();
 }
 
-/// globally enabling interrupts (IRQ/FIQ) to be triggered
+/// Core specific interrupt manager initialization. This need to be called once on each core other than the one that
+/// has called [initialize] and that shall handle interrupts. This is only required if the GIC-400 interrupt controller
+/// is used with the `pi4_gic` feature to configure the GIC CPU interface of this core. It does nothing otherwise.
+pub fn initialize_core() {
+  interface::initialize_core();
+}
+
+/// globally enabling interrupts (IRQ/FIQ) to be triggered. To mask the interrupts only for a critical section that
+/// might be nested use [critical_section] or the [InterruptGuard] instead.
 pub fn enable_interrupts() {
   interface::enable_irq();
   interface::enable_fiq();
@@ -128,9 +265,28 @@ pub fn disable_interrupts() {
   interface::disable_fiq();
 }
 
+/// Route the GPU (peripheral) interrupts to the given core. By default they are routed to core 0 as IRQ. The
+/// interrupts will only be dispatched on the core they are routed to.
+///
+/// The GPU IRQ and GPU FIQ routing are configured independently. On the Raspberry Pi4 the interrupts already activated
+/// for the given [RoutingMode] are moved to the new core. With the `pi4_gic` feature the interrupts can only be routed
+/// as IRQ.
+///
+/// # Panics
+/// The function panics if the core is not in the range of 0..=3.
+pub fn route_gpu_irqs(core: usize, mode: RoutingMode) {
+  assert!(core < 4, "core need to be in the range of 0..=3");
+  interface::route_gpu_irqs(core, mode);
+  #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
+  unsafe {
+    asm!("dmb sy")
+  };
+}
+
 /// Activate a specific interrupt to be raised and handled (if a handler is implemented).
 /// If there is no handler implemented for this interrupt it may lead to an endless interrupt
-/// loop as the interrupt never gets acknowledged by the handler.
+/// loop as the interrupt never gets acknowledged by the handler. The storm detection enabled with
+/// [configure_storm_detection] deactivates such an interrupt.
 /// There is unfortunately no generic way of acknowledgement implementation possible as the acknowledge
 /// register and process differs for the individual interrupts and thus need to be implemented in the specific
 /// handler.
@@ -142,24 +298,39 @@ pub fn disable_interrupts() {
 /// handler because this one should run as fast as possible.
 /// To register an interrupt handler for a shared interrupt line the specialized respective function should be used.
 ///
+/// The core specific interrupts like ``CntPsIrq`` are activated for core 0. With the `pi4_gic` feature those are
+/// activated for the core calling this function as the GIC allows each core to only activate it's own core specific
+/// interrupts.
+///
 /// # Panics
-/// The function panics if it is called for a known shared interrupt line
+/// The function panics if it is called for a known shared interrupt line like ``Aux``. The interrupt lines of the GPIO
+/// banks could be activated for the handler of the whole bank. It also panics if a mailbox interrupt like
+/// ``Core1Mailbox3`` is activated with a static channel that could not receive the message of the mailbox.
 ///
-pub fn activate(irq: Interrupt, channel: IsrChannel) {
-  // Aux interrupts share one interrupt line - thus special handling for setting the IsrSender
-  // Aux interrupt activation is done in a separate function
-  if irq == Interrupt::Aux {
-    panic!("AUX interrupts require activation with 'activate_aux'");
+pub fn activate<C: IntoIsrChannel>(irq: Interrupt, channel: C) {
+  // shared interrupt lines pass a channel to each of their sources - thus they are activated with the specialized
+  // function of the line, e.g. ``activate_aux``. Only the lines with a handler for the whole line, like the GPIO banks,
+  // could be activated for this handler.
+  if matches!(sharedline::shared_line(irq), Some(line) if line.line_handler.is_none()) {
+    panic!("shared interrupt lines require activation of their source, e.g. with 'activate_aux'");
+  }
+
+  // core specific interrupts are activated for the default core if not requested for a specific core
+  if core_local_slot(irq).is_some() {
+    return activate_on_core(irq, default_local_core(), channel);
   }
 
   let irq_bank = (irq as u32) >> 5;
   let irq_num = (irq as u32) & 0x1F;
+  let channel = channel.into_isr_channel();
+  ipi::check_channel(irq, &channel);
+  storm::release(irq);
 
-  ISR_LIST.0.get(irq_bank as usize).map(|bank| {
-    bank
-      .get(irq_num as usize)
-      .map(|(_, irq_channel)| *irq_channel.borrow_mut() = channel);
-  });
+  if let Some(bank) = ISR_LIST.0.get(irq_bank as usize) {
+    if let Some((_, slot)) = bank.get(irq_num as usize) {
+      slot.channel.replace(channel);
+    }
+  }
 
   interface::activate(irq);
   #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
@@ -168,11 +339,81 @@ pub fn activate(irq: Interrupt, channel: IsrChannel) {
   };
 }
 
+/// Activate a core specific interrupt to be raised and handled on the given core. The core specific interrupts are the
+/// generic timer interrupts ``CntPsIrq``, ``CntPnsIrq``, ``CntHpIrq`` and ``CntVIrq`` that exist for each core. The
+/// sender of the interrupt service routine channel is kept for each core individually. The handler of the interrupt
+/// will receive the sender that has been registered for the core it is executed on.
+///
+/// With the `pi4_gic` feature the core timer interrupts can only be activated by the core itself. So this function
+/// need to be called on the core given. Called on any other core the channel is registered but the interrupt is not
+/// activated.
+///
+/// # Panics
+/// The function panics if it is called for an interrupt that is not core specific or if the core is not in the range
+/// of 0..=3.
+pub fn activate_on_core<C: IntoIsrChannel>(irq: Interrupt, core: usize, channel: C) {
+  let slot = core_local_slot(irq).expect("interrupt can not be activated for a specific core");
+  let core_list = CORE_ISR_LIST.0.get(core).expect("core need to be in the range of 0..=3");
+  core_list[slot].replace(channel.into_isr_channel());
+  storm::release(irq);
+
+  interface::activate_on_core(irq, core);
+  #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
+  unsafe {
+    asm!("dmb sy")
+  };
+}
+
+/// Activate a specific interrupt to be raised as FIQ and handled by the handler implemented with the
+/// ``#[FiqHandler(...)]`` attribute. FIQ's are meant for the one ultra-low-latency interrupt source of the system.
+///
+/// On the Raspberry Pi3 only one GPU (peripheral) interrupt could be raised as FIQ. Activating another one replaces
+/// the previous interrupt. The GPU interrupts are raised as FIQ on the core the GPU FIQ's are routed to with
+/// [route_gpu_irqs]. The core timer interrupts are raised as FIQ on core 0, the core mailbox interrupts on the core they
+/// belong to. The ARM local timer can not be activated as FIQ with this function. With the `pi4_gic` feature FIQ's are
+/// not supported. The channel is registered but the interrupt will never be raised as FIQ.
+///
+/// The channel is passed to the FIQ handler the same way as for an interrupt handler. An interrupt should not be
+/// activated as IRQ and FIQ at the same time.
+///
+/// # Panics
+/// The function panics if it is called for a known shared interrupt line like ``Aux``. The interrupt lines of the GPIO
+/// banks could be activated for the handler of the whole bank. It also panics if a mailbox interrupt like
+/// ``Core1Mailbox3`` is activated with a static channel that could not receive the message of the mailbox.
+pub fn activate_fiq<C: IntoIsrChannel>(irq: Interrupt, channel: C) {
+  if matches!(sharedline::shared_line(irq), Some(line) if line.line_handler.is_none()) {
+    panic!("shared interrupt lines can not be activated as FIQ");
+  }
+
+  let channel = channel.into_isr_channel();
+  ipi::check_channel(irq, &channel);
+  channel_slot(irq, 0).replace(channel);
+  storm::release(irq);
+
+  interface::activate_fiq(irq);
+  #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
+  unsafe {
+    asm!("dmb sy")
+  };
+}
+
+/// deactivate a specific interrupt from beeing raised as FIQ.
+pub fn deactivate_fiq(irq: Interrupt) {
+  interface::deactivate_fiq(irq);
+  channel_slot(irq, 0).take();
+
+  #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
+  unsafe {
+    asm!("dmb sy")
+  };
+}
+
 /// Activate the AUX interrupt line. This line is shared between three aux devices. The miniUART, SPI1 and SPI2.
 /// The interrupts for those devices can't be enabled individually. However, we allow to register different IsrSender
 /// for the individual device as the interrupt provisioning is based on the AUXIRQ status register that indicates the
 /// correct device having raised the interrupt. The only way to suppress interrups for an individual device would
-/// require disabling of the device with the AUXENB register.
+/// require disabling of the device with the AUXENB register. The AUX interrupt line stays active until each activated
+/// device has been deactivated with [deactivate_aux].
 ///
 /// You might want to pass a sender of an interrupt service routine channel. This channel can be used within the
 /// interrupt handler implementation to pass data from the ISR to the normal processing. An example for this could be
@@ -180,30 +421,131 @@ pub fn activate(irq: Interrupt, channel: IsrChannel) {
 /// incomming data and push it into the channel for further processing that should take place outside of the interrupt
 /// handler because this one should run as fast as possible.
 /// To register an interrupt handler for a shared interrupt line the specialized respective function should be used.
-pub fn activate_aux(aux: AuxDevice, channel: IsrChannel) {
-  // Aux interrupts share one interrupt line - thus special handling for setting the IsrSender
-  set_aux_isrsender(aux, channel);
+pub fn activate_aux<C: IntoIsrChannel>(aux: AuxDevice, channel: C) {
+  // Aux interrupts share one interrupt line - thus the IsrSender is kept for each device
+  activate_shared_source(&auxhandler::AUX_LINE, aux as usize, channel.into_isr_channel());
+}
+
+/// deactivate the interrupt of a single aux device. The channel registered for the device is released. The AUX
+/// interrupt line is deactivated once the last of the aux devices is deactivated.
+///
+/// The interrupts of the device itself need to be disabled with it's interrupt enable register, otherwise a pending
+/// interrupt of the device is still passed to it's handler while other aux devices are active. The device is not
+/// disabled in the AUXENB register as it might still be used without interrupts.
+pub fn deactivate_aux(aux: AuxDevice) {
+  deactivate_shared_source(&auxhandler::AUX_LINE, aux as usize);
+}
+
+/// Activate the interrupt of the given DMA channel in the range of 0..=14. The handler of a DMA channel with it's own
+/// interrupt line is implemented with ``#[IrqHandler(DMA3)]``. The channels 7 and 8 share the interrupt line ``DMA7_8``
+/// and the channels 9 and 10 the interrupt line ``DMA9_10``. Their handler is implemented for the channel like
+/// ``#[IrqHandler(DMA7_8, Channel8)]`` and is called based on the DMA interrupt status register. The handler need to
+/// acknowledge the interrupt in the control and status register of the DMA channel.
+///
+/// # Panics
+/// The function panics if the DMA channel is not in the range of 0..=14
+pub fn activate_dma<C: IntoIsrChannel>(dma_channel: u8, channel: C) {
+  match dmahandler::channel_line(dma_channel) {
+    (_, Some((line, source))) => activate_shared_source(line, source, channel.into_isr_channel()),
+    (irq, None) => activate(irq, channel),
+  }
+}
+
+/// deactivate the interrupt of the given DMA channel. The interrupt line shared between two DMA channels is
+/// deactivated once both channels are deactivated.
+///
+/// # Panics
+/// The function panics if the DMA channel is not in the range of 0..=14
+pub fn deactivate_dma(dma_channel: u8) {
+  match dmahandler::channel_line(dma_channel) {
+    (_, Some((line, source))) => deactivate_shared_source(line, source),
+    (irq, None) => deactivate(irq),
+  }
+}
+
+/// Activate a source of a shared interrupt line with the channel passed to it's handler
+fn activate_shared_source(line: &SharedLine, source: usize, channel: IsrChannel) {
+  line.activate(source, channel);
+  storm::release(line.irq);
+
+  interface::activate(line.irq);
+  #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
+  unsafe {
+    asm!("dmb sy")
+  };
+}
+
+/// deactivate a source of a shared interrupt line. The line is deactivated once the last of it's sources is
+/// deactivated.
+fn deactivate_shared_source(line: &SharedLine, source: usize) {
+  if line.deactivate(source) {
+    interface::deactivate(line.irq);
+    #[cfg(feature = "nesting")]
+    nesting::forget(line.irq);
+  }
 
-  interface::activate(Interrupt::Aux);
   #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
   unsafe {
     asm!("dmb sy")
   };
 }
 
+/// Activate the interrupt of a single GPIO pin. The GPIO pins share the interrupt line of their bank: ``GpioBank0`` for
+/// the pins 0..=27, ``GpioBank1`` for the pins 28..=45 and ``GpioBank2`` for the remaining ones. The detection of the
+/// given event is enabled for the pin and the interrupt line of it's bank is activated. A detected event is
+/// acknowledged before the handler implemented with ``#[IrqHandler(GpioBank0, Pin17)]`` is called with the given
+/// channel.
+///
+/// Events of pins that are not activated with this function are passed to the handler of the whole bank implemented
+/// with ``#[IrqHandler(GpioBank0)]`` which need to acknowledge them itself. A handler registered at runtime for the
+/// interrupt line of the bank replaces the dispatching to the pins. Deactivating the interrupt line of the bank with
+/// [deactivate] deactivates all of it's pins.
+///
+/// # Panics
+/// The function panics if the pin does not exist
+pub fn activate_gpio_pin<C: IntoIsrChannel>(pin: u8, edge: GpioEdge, channel: C) {
+  let (line, source) = gpiohandler::pin_line(pin);
+  gpiohandler::enable_pin(pin, edge);
+  activate_shared_source(line, source, channel.into_isr_channel());
+}
+
+/// deactivate the interrupt of a single GPIO pin. The event detection of the pin is disabled. Once the last pin of a
+/// bank is deactivated the interrupt line of the bank is deactivated as well.
+///
+/// # Panics
+/// The function panics if the pin does not exist
+pub fn deactivate_gpio_pin(pin: u8) {
+  let (line, source) = gpiohandler::pin_line(pin);
+  deactivate_shared_source(line, source);
+}
+
 /// deactivate a specific interrupt from beeing raised. This ensures the handler will also not getting called any
 /// longer
+/// For a shared interrupt line like ``Aux`` all of it's sources are deactivated and their channels are released. The
+/// event detection of the pins of a GPIO bank is disabled this way. The core specific
+/// interrupts are deactivated for the same core [activate] has activated them for.
 pub fn deactivate(irq: Interrupt) {
+  // core specific interrupts are deactivated for the default core if not requested for a specific core
+  if core_local_slot(irq).is_some() {
+    return deactivate_on_core(irq, default_local_core());
+  }
+
   interface::deactivate(irq);
+  #[cfg(feature = "nesting")]
+  nesting::forget(irq);
 
   let irq_bank = (irq as u32) >> 5;
   let irq_num = (irq as u32) & 0x1F;
 
-  ISR_LIST.0.get(irq_bank as usize).map(|bank| {
-    bank
-      .get(irq_num as usize)
-      .map(|(_, irq_channel)| irq_channel.borrow_mut().take());
-  });
+  if let Some(bank) = ISR_LIST.0.get(irq_bank as usize) {
+    if let Some((_, slot)) = bank.get(irq_num as usize) {
+      slot.channel.take();
+    }
+  }
+  // the sources of a shared interrupt line are deactivated and release their channels as well
+  if let Some(line) = sharedline::shared_line(irq) {
+    line.release();
+  }
 
   #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
   unsafe {
@@ -211,6 +553,100 @@ pub fn deactivate(irq: Interrupt) {
   };
 }
 
+/// deactivate a core specific interrupt from beeing raised on the given core. The handler will no longer be called on
+/// this core. With the `pi4_gic` feature the interrupt is only deactivated if this function is called on the core
+/// given.
+///
+/// # Panics
+/// The function panics if it is called for an interrupt that is not core specific or if the core is not in the range
+/// of 0..=3.
+pub fn deactivate_on_core(irq: Interrupt, core: usize) {
+  let slot = core_local_slot(irq).expect("interrupt can not be deactivated for a specific core");
+  let core_list = CORE_ISR_LIST.0.get(core).expect("core need to be in the range of 0..=3");
+
+  interface::deactivate_on_core(irq, core);
+  core_list[slot].take();
+
+  #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
+  unsafe {
+    asm!("dmb sy")
+  };
+}
+
+/// Register a handler for the given interrupt at runtime. The handler may capture the state it requires to handle the
+/// interrupt. Once registered it is called instead of the handler implemented with the ``#[IrqHandler(...)]`` or
+/// ``#[FiqHandler(...)]`` attribute which remains the fallback if no handler is registered. Registering a handler
+/// replaces the one previously registered for this interrupt.
+///
+/// The interrupt still need to be activated with the corresponding function to be raised. A handler registered for the
+/// shared ``Aux`` interrupt line is called for all of the aux devices instead of the device specific handler.
+///
+/// The handler is called on one core at a time. The same interrupt dispatched on another core meanwhile waits for it to
+/// return. If it registers or unregisters a handler for the interrupt it is handling this takes effect with the next
+/// interrupt. The handler replaced this way is dropped with the next registration outside of the interrupt handling.
+pub fn register_handler(irq: Interrupt, handler: Box<dyn FnMut() + Send>) {
+  let irq_bank = (irq as usize) >> 5;
+  let irq_num = (irq as usize) & 0x1F;
+
+  let slot = &ISR_LIST.0[irq_bank][irq_num].1;
+  slot.handler.replace(Some(handler));
+  #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
+  unsafe {
+    asm!("dmb sy")
+  };
+}
+
+/// Unregister the handler registered at runtime for the given interrupt. The handler implemented with the
+/// ``#[IrqHandler(...)]`` or ``#[FiqHandler(...)]`` attribute will be called again.
+pub fn unregister_handler(irq: Interrupt) {
+  let irq_bank = (irq as usize) >> 5;
+  let irq_num = (irq as usize) & 0x1F;
+
+  let slot = &ISR_LIST.0[irq_bank][irq_num].1;
+  slot.handler.take();
+  #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
+  unsafe {
+    asm!("dmb sy")
+  };
+}
+
+/// Get the slot of the core specific interrupt within the per core channel list. The core specific interrupts are
+/// placed at the beginning of the core interrupt bank.
+fn core_local_slot(irq: Interrupt) -> Option<usize> {
+  match irq {
+    Interrupt::CntPsIrq | Interrupt::CntPnsIrq | Interrupt::CntHpIrq | Interrupt::CntVIrq => {
+      Some((irq as usize) & 0x1F)
+    }
+    _ => None,
+  }
+}
+
+/// Get the core the core specific interrupts are (de)activated for if not requested for a specific core. The GIC enables
+/// those in a register banked for each core, so they are (de)activated for the calling core there.
+fn default_local_core() -> usize {
+  if cfg!(all(feature = "pi4_gic", not(feature = "sim"))) {
+    interface::core_id()
+  } else {
+    0
+  }
+}
+
+/// Get the channel of the interrupt handler. Core specific interrupts use the channel registered for the given core.
+fn channel_slot(irq: Interrupt, core: usize) -> &'static IsrCell<ErasedSender> {
+  let bank = (irq as usize) >> 5;
+  let num = (irq as usize) & 0x1F;
+  dispatch_channel(core, bank, num)
+}
+
+/// Get the channel to be passed to the handler of the interrupt at the given bank and bit position on the given core
+fn dispatch_channel(core: usize, bank: usize, num: usize) -> &'static IsrCell<ErasedSender> {
+  if bank == CORE_BANK && num < CORE_LOCAL_IRQS {
+    &CORE_ISR_LIST.0[core][num]
+  } else {
+    &ISR_LIST.0[bank][num].1.channel
+  }
+}
+
 /********************************************************************************************
  * Functions that need to be exported, this seem not to work if they are part of of a child
  * module, so define them here
@@ -218,24 +654,193 @@ pub fn deactivate(irq: Interrupt) {
 #[no_mangle]
 unsafe extern "C" fn __isr_default() {
   // now retrieve the pending interrupts (already filtered by the active one)
-  let pendings = interface::get_pending_irqs();
+  let pendings = without_core_gpu(interface::get_pending_irqs());
+  let core = interface::core_id();
+  if pendings.iter().all(|&pending| pending == 0) {
+    SPURIOUS_IRQS.fetch_add(1, Ordering::Relaxed);
+  }
   // now dispatch the interrupts to their respective handler
-  for (&pending_bank, handler_bank) in pendings.iter().zip(ISR_LIST.0.iter()) {
+  #[cfg(not(feature = "nesting"))]
+  for (bank, &pending_bank) in pendings.iter().enumerate() {
+    for irq in bitset::BitSet32(pending_bank).iter() {
+      dispatch_irq(core, bank, irq as usize);
+    }
+  }
+  // with the software interrupt nesting the interrupts are dispatched by their priority and could be preempted
+  #[cfg(feature = "nesting")]
+  nesting::dispatch(core, pendings);
+  // signal the end of the interrupt to the interrupt controller if required
+  interface::end_of_interrupt();
+}
+
+/// The GPU bit of the core specific bank only signals that GPU interrupts are pending on the current core. Those are
+/// dispatched with their own bank, so the bit is removed once the interrupt controller has used it to read the GPU
+/// banks. Otherwise each GPU interrupt would be reported as unhandled ``CoreGPU`` interrupt.
+fn without_core_gpu(mut pendings: [u32; 4]) -> [u32; 4] {
+  pendings[CORE_BANK] &= !(1 << (Interrupt::CoreGPU as u32 & 0x1F));
+  pendings
+}
+
+/// Dispatch the pending interrupt at the given bank and bit position to it's handler
+fn dispatch_irq(core: usize, bank: usize, num: usize) {
+  let (handler, slot) = &ISR_LIST.0[bank][num];
+  // there is no handler for a pending interrupt that is not known
+  let irq_number = ((bank as u32) << 5) + num as u32;
+  if Interrupt::try_from(irq_number as u8).is_err() {
+    report_unhandled(irq_number);
+    return;
+  }
+  // an interrupt storm deactivates the interrupt instead of calling it's handler once again
+  if storm::check(bank, num, core, RoutingMode::Irq) {
+    return;
+  }
+  #[cfg(feature = "stats")]
+  let start = stats::start();
+  // the message of an inter-processor interrupt is received before the handler is called
+  if let Some((ipi_core, mailbox)) = ipi::ipi_mailbox(bank, num) {
+    ipi::receive(ipi_core, mailbox, dispatch_channel(core, bank, num));
+  }
+  localtimer::acknowledge(bank, num);
+  // a handler registered at runtime takes precedence over the one implemented with the `IrqHandler` attribute
+  if !dispatch_runtime_handler(slot) {
+    // core specific interrupts use the channel registered for the current core
+    dispatch_channel(core, bank, num).lend(|channel| handler(channel));
+  }
+  #[cfg(feature = "stats")]
+  stats::record(bank, num, start);
+}
+
+/// Call the handler registered at runtime for the interrupt of the given slot. Returns ``false`` if there is no such
+/// handler registered.
+fn dispatch_runtime_handler(slot: &IsrSlot) -> bool {
+  slot.handler.lend_mut(|handler| match handler {
+    Some(handler) => {
+      handler();
+      true
+    }
+    None => false,
+  })
+}
+
+#[no_mangle]
+unsafe extern "C" fn __fiq_default() {
+  // retrieve the pending interrupts raised as FIQ
+  let pendings = without_core_gpu(interface::get_pending_fiqs());
+  let core = interface::core_id();
+  if pendings.iter().all(|&pending| pending == 0) {
+    SPURIOUS_FIQS.fetch_add(1, Ordering::Relaxed);
+  }
+  // dispatch the interrupts to their respective FIQ handler
+  for (bank, (&pending_bank, handler_bank)) in pendings.iter().zip(FIQ_LIST.0.iter()).enumerate() {
     for irq in bitset::BitSet32(pending_bank).iter() {
-      handler_bank.get(irq as usize).map(|(handler, channel)| {
-        handler(channel.borrow().clone());
-      });
+      let irq_number = ((bank as u32) << 5) + irq;
+      if Interrupt::try_from(irq_number as u8).is_err() {
+        report_unhandled(irq_number);
+        continue;
+      }
+      if storm::check(bank, irq as usize, core, RoutingMode::Fiq) {
+        continue;
+      }
+      #[cfg(feature = "stats")]
+      let start = stats::start();
+      if let Some((ipi_core, mailbox)) = ipi::ipi_mailbox(bank, irq as usize) {
+        ipi::receive(
+          ipi_core,
+          mailbox,
+          dispatch_channel(core, bank, irq as usize),
+        );
+      }
+      localtimer::acknowledge(bank, irq as usize);
+      // a handler registered at runtime takes precedence over the one implemented with the `FiqHandler` attribute
+      if !dispatch_runtime_handler(&ISR_LIST.0[bank][irq as usize].1) {
+        let handler = handler_bank[irq as usize];
+        dispatch_channel(core, bank, irq as usize).lend(|channel| handler(channel));
+      }
+      #[cfg(feature = "stats")]
+      stats::record(bank, irq as usize, start);
     }
   }
 }
 
+/// The hook called for a pending interrupt that has no handler implemented. The ``irq_number`` is the number of the
+/// interrupt as used with [Interrupt], the ``bank`` is the bank of the pending register the interrupt belongs to. The
+/// default implementation does nothing. It could be overridden to report or deactivate the unknown interrupt source
+/// like this:
+///
+/// ```ignore
+/// #[no_mangle]
+/// extern "C" fn unhandled_interrupt(irq_number: u32, bank: u32) {
+///   // report the interrupt or deactivate it
+/// }
+/// ```
+#[linkage = "weak"]
+#[no_mangle]
+pub extern "C" fn unhandled_interrupt(_irq_number: u32, _bank: u32) {}
+
+/// The number of pending interrupts that had no handler implemented
+static UNHANDLED: AtomicUsize = AtomicUsize::new(0);
+/// The number of entries into the IRQ handling without a pending interrupt
+static SPURIOUS_IRQS: AtomicUsize = AtomicUsize::new(0);
+/// The number of entries into the FIQ handling without a pending interrupt
+static SPURIOUS_FIQS: AtomicUsize = AtomicUsize::new(0);
+
+/// The number of pending interrupts that had no handler implemented. For each of them [unhandled_interrupt] has been
+/// called.
+pub fn unhandled_count() -> usize {
+  UNHANDLED.load(Ordering::Relaxed)
+}
+
+/// The number of spurious interrupts of the given kind. Those are entries into the interrupt handling while no activated
+/// interrupt has been pending.
+pub fn spurious_count(mode: RoutingMode) -> usize {
+  match mode {
+    RoutingMode::Irq => SPURIOUS_IRQS.load(Ordering::Relaxed),
+    RoutingMode::Fiq => SPURIOUS_FIQS.load(Ordering::Relaxed),
+  }
+}
+
+/// Count the pending interrupt without a handler and pass it to the [unhandled_interrupt] hook
+fn report_unhandled(irq_number: u32) {
+  UNHANDLED.fetch_add(1, Ordering::Relaxed);
+  unhandled_interrupt(irq_number, irq_number >> 5);
+}
+
+/// The default handlers report the interrupt as unhandled. They are replaced by the linker with the handlers
+/// implemented with the ``IrqHandler`` and ``FiqHandler`` attribute. The handlers of the shared interrupt line sources
+/// report the interrupt line. The interrupts acknowledged by this crate, like the inter-processor interrupts, do not
+/// require a handler, so their default handlers do nothing.
 macro_rules! default_handler_impl {
     ($($name:ident),*) => {$(
+        default_handler_impl!($name => $name);
+    )*};
+    ($($name:ident => $irq:ident),*) => {$(
         paste::item!{
             #[allow(non_snake_case, improper_ctypes_definitions)]
             #[linkage="weak"]
             #[no_mangle]
-            extern "C" fn [<__irq_handler__ $name>](_tx: IsrChannel){}
+            extern "C" fn [<__irq_handler__ $name>](_tx: &IsrChannel){
+              report_unhandled(Interrupt::$irq as u32);
+            }
+
+            #[allow(non_snake_case, improper_ctypes_definitions)]
+            #[linkage="weak"]
+            #[no_mangle]
+            extern "C" fn [<__fiq_handler__ $name>](_tx: &IsrChannel){
+              report_unhandled(Interrupt::$irq as u32);
+            }
+        }
+    )*};
+    (@acknowledged $($name:ident),*) => {$(
+        paste::item!{
+            #[allow(non_snake_case, improper_ctypes_definitions)]
+            #[linkage="weak"]
+            #[no_mangle]
+            extern "C" fn [<__irq_handler__ $name>](_tx: &IsrChannel){}
+
+            #[allow(non_snake_case, improper_ctypes_definitions)]
+            #[linkage="weak"]
+            #[no_mangle]
+            extern "C" fn [<__fiq_handler__ $name>](_tx: &IsrChannel){}
         }
     )*};
 }
@@ -249,11 +854,36 @@ default_handler_impl![
   CoreSync1,
   CoreSync2,
   CoreSync3,
-  Aux_Uart1,
-  Aux_Spi1,
-  Aux_Spi2,
+  DMA0,
+  DMA1,
+  DMA2,
+  DMA3,
+  DMA4,
+  DMA5,
+  DMA6,
+  DMA11,
+  DMA12,
+  DMA13,
+  DMA14,
   Arm,
   GpuDma,
+  HostPort,
+  VideoScaler,
+  Ccp2Tx,
+  Sdc,
+  Dsi0,
+  Ave,
+  Cam0,
+  Cam1,
+  Hdmi0,
+  Hdmi1,
+  PixelValve3,
+  I2cSpi,
+  Dsi1,
+  Pwa0,
+  Pwa1,
+  Cpr,
+  Smi,
   GpioBank0,
   GpioBank1,
   GpioBank2,
@@ -277,157 +907,350 @@ default_handler_impl![
   CntPnsIrq,
   CntHpIrq,
   CntVIrq,
+  CoreGPU,
+  LocalTimer
+];
+
+default_handler_impl![@acknowledged
+  Core0Mailbox0,
+  Core1Mailbox0,
+  Core2Mailbox0,
+  Core3Mailbox0,
+  Core0Mailbox1,
+  Core1Mailbox1,
+  Core2Mailbox1,
+  Core3Mailbox1,
+  Core0Mailbox2,
+  Core1Mailbox2,
+  Core2Mailbox2,
+  Core3Mailbox2,
   Core0Mailbox3,
   Core1Mailbox3,
   Core2Mailbox3,
-  Core3Mailbox3,
-  CoreGPU,
-  LocalTimer
+  Core3Mailbox3
 ];
 
+ruspiro_interrupt_macros::shared_line_handlers!();
+
+#[allow(non_snake_case, improper_ctypes_definitions)]
+#[no_mangle]
+extern "C" fn __irq_handler_Default(_channel: &IsrChannel) {}
+
 #[allow(non_snake_case, improper_ctypes_definitions)]
 #[no_mangle]
-extern "C" fn __irq_handler_Default(_channel: IsrChannel) {}
+extern "C" fn __fiq_handler_Default(_channel: &IsrChannel) {}
+
+struct IsrList([[(extern "C" fn(&IsrChannel), IsrSlot); 32]; 4]);
+
+/// The channel and the runtime handler registered for an interrupt. Both could be replaced from any core while the
+/// interrupt is dispatched.
+struct IsrSlot {
+  channel: IsrCell<ErasedSender>,
+  handler: IsrCell<IsrHandler>,
+}
+
+impl IsrSlot {
+  const fn new() -> Self {
+    Self {
+      channel: IsrCell::new(),
+      handler: IsrCell::new(),
+    }
+  }
+}
+
+/// The bank of the core specific interrupts
+const CORE_BANK: usize = 3;
+/// The number of interrupts at the beginning of the core specific bank that could be activated for each core
+const CORE_LOCAL_IRQS: usize = 4;
 
-struct IsrList([[(extern "C" fn(IsrChannel), RefCell<IsrChannel>); 32]; 4]);
-unsafe impl Sync for IsrList {}
+struct CoreIsrList([[IsrCell<ErasedSender>; CORE_LOCAL_IRQS]; 4]);
+
+/// The list of interrupt service routine channels of the core specific interrupts for each core
+static CORE_ISR_LIST: CoreIsrList = {
+  // the constant is only used as the initial value of the channels of each core
+  #[allow(clippy::declare_interior_mutable_const)]
+  const CORE_CHANNELS: [IsrCell<ErasedSender>; CORE_LOCAL_IRQS] = [IsrCell::EMPTY; CORE_LOCAL_IRQS];
+  CoreIsrList([CORE_CHANNELS; 4])
+};
 
 /// The list of interrupt service routines
 static ISR_LIST: IsrList = IsrList([
   [
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler__SystemTimer1, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler__SystemTimer3, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler__Isp, RefCell::new(None)),
-    (__irq_handler__Usb, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)), //10
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler__CoreSync0, RefCell::new(None)),
-    (__irq_handler__CoreSync1, RefCell::new(None)),
-    (__irq_handler__CoreSync2, RefCell::new(None)),
-    (__irq_handler__CoreSync3, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)), //20
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (auxhandler::aux_handler, RefCell::new(None)),
-    (__irq_handler__Arm, RefCell::new(None)), //30
-    (__irq_handler__GpuDma, RefCell::new(None)),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler__SystemTimer1, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler__SystemTimer3, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler__Isp, IsrSlot::new()),
+    (__irq_handler__Usb, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()), //10
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler__CoreSync0, IsrSlot::new()),
+    (__irq_handler__CoreSync1, IsrSlot::new()),
+    (__irq_handler__CoreSync2, IsrSlot::new()),
+    (__irq_handler__CoreSync3, IsrSlot::new()),
+    (__irq_handler__DMA0, IsrSlot::new()),
+    (__irq_handler__DMA1, IsrSlot::new()),
+    (__irq_handler__DMA2, IsrSlot::new()),
+    (__irq_handler__DMA3, IsrSlot::new()),
+    (__irq_handler__DMA4, IsrSlot::new()), //20
+    (__irq_handler__DMA5, IsrSlot::new()),
+    (__irq_handler__DMA6, IsrSlot::new()),
+    (dmahandler::dma7_8_handler, IsrSlot::new()),
+    (dmahandler::dma9_10_handler, IsrSlot::new()),
+    (__irq_handler__DMA11, IsrSlot::new()),
+    (__irq_handler__DMA12, IsrSlot::new()),
+    (__irq_handler__DMA13, IsrSlot::new()),
+    (__irq_handler__DMA14, IsrSlot::new()),
+    (auxhandler::aux_handler, IsrSlot::new()),
+    (__irq_handler__Arm, IsrSlot::new()), //30
+    (__irq_handler__GpuDma, IsrSlot::new()),
+  ],
+  [
+    (__irq_handler__HostPort, IsrSlot::new()),
+    (__irq_handler__VideoScaler, IsrSlot::new()),
+    (__irq_handler__Ccp2Tx, IsrSlot::new()),
+    (__irq_handler__Sdc, IsrSlot::new()),
+    (__irq_handler__Dsi0, IsrSlot::new()),
+    (__irq_handler__Ave, IsrSlot::new()),
+    (__irq_handler__Cam0, IsrSlot::new()),
+    (__irq_handler__Cam1, IsrSlot::new()),
+    (__irq_handler__Hdmi0, IsrSlot::new()), // 40
+    (__irq_handler__Hdmi1, IsrSlot::new()),
+    (__irq_handler__PixelValve3, IsrSlot::new()),
+    (__irq_handler__I2cSpi, IsrSlot::new()),
+    (__irq_handler__Dsi1, IsrSlot::new()),
+    (__irq_handler__Pwa0, IsrSlot::new()),
+    (__irq_handler__Pwa1, IsrSlot::new()),
+    (__irq_handler__Cpr, IsrSlot::new()),
+    (__irq_handler__Smi, IsrSlot::new()),
+    (gpiohandler::gpio_bank0_handler, IsrSlot::new()),
+    (gpiohandler::gpio_bank1_handler, IsrSlot::new()), // 50
+    (gpiohandler::gpio_bank2_handler, IsrSlot::new()),
+    (__irq_handler__GpioBank3, IsrSlot::new()),
+    (__irq_handler__I2c, IsrSlot::new()),
+    (__irq_handler__Spi, IsrSlot::new()),
+    (__irq_handler__I2sPcm, IsrSlot::new()),
+    (__irq_handler__Sdio, IsrSlot::new()),
+    (__irq_handler__Pl011, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()), // 60
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+  ],
+  [
+    (__irq_handler__ArmTimer, IsrSlot::new()),
+    (__irq_handler__ArmMailbox, IsrSlot::new()),
+    (__irq_handler__ArmDoorbell0, IsrSlot::new()),
+    (__irq_handler__ArmDoorbell1, IsrSlot::new()),
+    (__irq_handler__ArmGpu0Halted, IsrSlot::new()),
+    (__irq_handler__ArmGpu1Halted, IsrSlot::new()),
+    (__irq_handler__ArmIllegalType1, IsrSlot::new()), // 70
+    (__irq_handler__ArmIllegalType0, IsrSlot::new()),
+    (__irq_handler__ArmPending1, IsrSlot::new()),
+    (__irq_handler__ArmPending2, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()), // 80
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()), // 90
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+  ],
+  [
+    (__irq_handler__CntPsIrq, IsrSlot::new()),
+    (__irq_handler__CntPnsIrq, IsrSlot::new()),
+    (__irq_handler__CntHpIrq, IsrSlot::new()),
+    (__irq_handler__CntVIrq, IsrSlot::new()),
+    (__irq_handler__Core0Mailbox3, IsrSlot::new()), // 100
+    (__irq_handler__Core1Mailbox3, IsrSlot::new()),
+    (__irq_handler__Core2Mailbox3, IsrSlot::new()),
+    (__irq_handler__Core3Mailbox3, IsrSlot::new()),
+    (__irq_handler__CoreGPU, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler__LocalTimer, IsrSlot::new()),
+    (__irq_handler__Core0Mailbox0, IsrSlot::new()),
+    (__irq_handler__Core1Mailbox0, IsrSlot::new()),
+    (__irq_handler__Core2Mailbox0, IsrSlot::new()), // 110
+    (__irq_handler__Core3Mailbox0, IsrSlot::new()),
+    (__irq_handler__Core0Mailbox1, IsrSlot::new()),
+    (__irq_handler__Core1Mailbox1, IsrSlot::new()),
+    (__irq_handler__Core2Mailbox1, IsrSlot::new()),
+    (__irq_handler__Core3Mailbox1, IsrSlot::new()),
+    (__irq_handler__Core0Mailbox2, IsrSlot::new()),
+    (__irq_handler__Core1Mailbox2, IsrSlot::new()),
+    (__irq_handler__Core2Mailbox2, IsrSlot::new()),
+    (__irq_handler__Core3Mailbox2, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()), // 120
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+    (__irq_handler_Default, IsrSlot::new()),
+  ],
+]);
+
+struct FiqList([[extern "C" fn(&IsrChannel); 32]; 4]);
+
+/// The list of the FIQ handlers. The shared Aux interrupt line can not be raised as FIQ
+static FIQ_LIST: FiqList = FiqList([
+  [
+    __fiq_handler_Default,
+    __fiq_handler__SystemTimer1,
+    __fiq_handler_Default,
+    __fiq_handler__SystemTimer3,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler__Isp,
+    __fiq_handler__Usb,
+    __fiq_handler_Default, //10
+    __fiq_handler_Default,
+    __fiq_handler__CoreSync0,
+    __fiq_handler__CoreSync1,
+    __fiq_handler__CoreSync2,
+    __fiq_handler__CoreSync3,
+    __fiq_handler__DMA0,
+    __fiq_handler__DMA1,
+    __fiq_handler__DMA2,
+    __fiq_handler__DMA3,
+    __fiq_handler__DMA4, //20
+    __fiq_handler__DMA5,
+    __fiq_handler__DMA6,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler__DMA11,
+    __fiq_handler__DMA12,
+    __fiq_handler__DMA13,
+    __fiq_handler__DMA14,
+    __fiq_handler_Default,
+    __fiq_handler__Arm, //30
+    __fiq_handler__GpuDma,
   ],
   [
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)), // 40
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler__GpioBank0, RefCell::new(None)),
-    (__irq_handler__GpioBank1, RefCell::new(None)), // 50
-    (__irq_handler__GpioBank2, RefCell::new(None)),
-    (__irq_handler__GpioBank3, RefCell::new(None)),
-    (__irq_handler__I2c, RefCell::new(None)),
-    (__irq_handler__Spi, RefCell::new(None)),
-    (__irq_handler__I2sPcm, RefCell::new(None)),
-    (__irq_handler__Sdio, RefCell::new(None)),
-    (__irq_handler__Pl011, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)), // 60
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
+    __fiq_handler__HostPort,
+    __fiq_handler__VideoScaler,
+    __fiq_handler__Ccp2Tx,
+    __fiq_handler__Sdc,
+    __fiq_handler__Dsi0,
+    __fiq_handler__Ave,
+    __fiq_handler__Cam0,
+    __fiq_handler__Cam1,
+    __fiq_handler__Hdmi0, // 40
+    __fiq_handler__Hdmi1,
+    __fiq_handler__PixelValve3,
+    __fiq_handler__I2cSpi,
+    __fiq_handler__Dsi1,
+    __fiq_handler__Pwa0,
+    __fiq_handler__Pwa1,
+    __fiq_handler__Cpr,
+    __fiq_handler__Smi,
+    __fiq_handler__GpioBank0,
+    __fiq_handler__GpioBank1, // 50
+    __fiq_handler__GpioBank2,
+    __fiq_handler__GpioBank3,
+    __fiq_handler__I2c,
+    __fiq_handler__Spi,
+    __fiq_handler__I2sPcm,
+    __fiq_handler__Sdio,
+    __fiq_handler__Pl011,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default, // 60
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
   ],
   [
-    (__irq_handler__ArmTimer, RefCell::new(None)),
-    (__irq_handler__ArmMailbox, RefCell::new(None)),
-    (__irq_handler__ArmDoorbell0, RefCell::new(None)),
-    (__irq_handler__ArmDoorbell1, RefCell::new(None)),
-    (__irq_handler__ArmGpu0Halted, RefCell::new(None)),
-    (__irq_handler__ArmGpu1Halted, RefCell::new(None)),
-    (__irq_handler__ArmIllegalType1, RefCell::new(None)), // 70
-    (__irq_handler__ArmIllegalType0, RefCell::new(None)),
-    (__irq_handler__ArmPending1, RefCell::new(None)),
-    (__irq_handler__ArmPending2, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)), // 80
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)), // 90
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
+    __fiq_handler__ArmTimer,
+    __fiq_handler__ArmMailbox,
+    __fiq_handler__ArmDoorbell0,
+    __fiq_handler__ArmDoorbell1,
+    __fiq_handler__ArmGpu0Halted,
+    __fiq_handler__ArmGpu1Halted,
+    __fiq_handler__ArmIllegalType1, // 70
+    __fiq_handler__ArmIllegalType0,
+    __fiq_handler__ArmPending1,
+    __fiq_handler__ArmPending2,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default, // 80
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default, // 90
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
   ],
   [
-    (__irq_handler__CntPsIrq, RefCell::new(None)),
-    (__irq_handler__CntPnsIrq, RefCell::new(None)),
-    (__irq_handler__CntHpIrq, RefCell::new(None)),
-    (__irq_handler__CntVIrq, RefCell::new(None)),
-    (__irq_handler__Core0Mailbox3, RefCell::new(None)), // 100
-    (__irq_handler__Core1Mailbox3, RefCell::new(None)),
-    (__irq_handler__Core2Mailbox3, RefCell::new(None)),
-    (__irq_handler__Core3Mailbox3, RefCell::new(None)),
-    (__irq_handler__CoreGPU, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler__LocalTimer, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)), // 110
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)), // 120
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
-    (__irq_handler_Default, RefCell::new(None)),
+    __fiq_handler__CntPsIrq,
+    __fiq_handler__CntPnsIrq,
+    __fiq_handler__CntHpIrq,
+    __fiq_handler__CntVIrq,
+    __fiq_handler__Core0Mailbox3, // 100
+    __fiq_handler__Core1Mailbox3,
+    __fiq_handler__Core2Mailbox3,
+    __fiq_handler__Core3Mailbox3,
+    __fiq_handler__CoreGPU,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler__LocalTimer,
+    __fiq_handler__Core0Mailbox0,
+    __fiq_handler__Core1Mailbox0,
+    __fiq_handler__Core2Mailbox0, // 110
+    __fiq_handler__Core3Mailbox0,
+    __fiq_handler__Core0Mailbox1,
+    __fiq_handler__Core1Mailbox1,
+    __fiq_handler__Core2Mailbox1,
+    __fiq_handler__Core3Mailbox1,
+    __fiq_handler__Core0Mailbox2,
+    __fiq_handler__Core1Mailbox2,
+    __fiq_handler__Core2Mailbox2,
+    __fiq_handler__Core3Mailbox2,
+    __fiq_handler_Default, // 120
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
+    __fiq_handler_Default,
   ],
 ]);
@@ -0,0 +1,70 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # ARM local timer
+//!
+//! The local timer is a 28 bit down counter running with the reference clock of the ARM local peripherals (38.4MHz on
+//! the Raspberry Pi3 and 54MHz on the Raspberry Pi4). Each time the counter reaches 0 it is reloaded and the
+//! ``LocalTimer`` interrupt is raised on the core it is routed to. The interrupt is acknowledged before the handler is
+//! called, so the handler does not need to do this.
+//!
+//! ```no_run
+//! # use ruspiro_interrupt::*;
+//! // raise the local timer interrupt every 100ms on core 1
+//! route_local_timer(1, RoutingMode::Irq);
+//! set_local_timer_reload(3_840_000);
+//! activate(Interrupt::LocalTimer, None);
+//! start_local_timer();
+//! ```
+//!
+
+use crate::{interface, Interrupt, RoutingMode, CORE_BANK};
+
+/// Set the value the local timer counts down from. The new value is used the next time the counter is reloaded.
+///
+/// # Panics
+/// The function panics if the value does not fit into the 28 bits of the counter.
+pub fn set_local_timer_reload(reload: u32) {
+  assert!(
+    reload <= interface::LOCAL_TIMER_MAX_RELOAD,
+    "local timer reload value need to be in the range of 0..=0x0FFF_FFFF"
+  );
+  interface::set_local_timer_reload(reload);
+}
+
+/// Start the local timer. The ``LocalTimer`` interrupt is raised each time the counter reaches 0 if it is activated.
+pub fn start_local_timer() {
+  interface::enable_local_timer(true);
+}
+
+/// Stop the local timer.
+pub fn stop_local_timer() {
+  interface::enable_local_timer(false);
+}
+
+/// Route the local timer interrupt to the given core as IRQ or FIQ. By default it is routed to core 0 as IRQ. The
+/// interrupt is activated with [activate](crate::activate) for both kinds of exceptions, the handler need to be
+/// implemented with the ``IrqHandler`` or ``FiqHandler`` attribute that matches the routing. With the `pi4_gic`
+/// feature the interrupt can only be routed as IRQ.
+///
+/// # Panics
+/// The function panics if the core is not in the range of 0..=3.
+pub fn route_local_timer(core: usize, mode: RoutingMode) {
+  assert!(core < 4, "core need to be in the range of 0..=3");
+  interface::route_local_timer(core, mode);
+  #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
+  unsafe {
+    asm!("dmb sy")
+  };
+}
+
+/// Acknowledge the local timer interrupt if it is the one at the given bank and bit position
+pub(crate) fn acknowledge(bank: usize, num: usize) {
+  if bank == CORE_BANK && num == (Interrupt::LocalTimer as usize & 0x1F) {
+    interface::acknowledge_local_timer();
+  }
+}
@@ -0,0 +1,136 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # Software interrupt nesting
+//!
+//! The legacy interrupt controller does not support priorities. With the `nesting` feature each interrupt is assigned
+//! a software priority with [set_priority]. The pending interrupts are dispatched starting with the highest priority.
+//! While the handler of a GPU (peripheral) interrupt is running, the GPU interrupts of lower or equal priority are
+//! disabled at the interrupt controller and IRQ's are enabled on the core. This allows interrupts of higher priority to
+//! preempt the handler. Once the handler returns IRQ's are masked again and the disabled interrupts are enabled again.
+//!
+//! ```no_run
+//! # use ruspiro_interrupt::*;
+//! // the timer handler shall not be delayed by the slow SPI handler
+//! set_priority(Interrupt::SystemTimer1, 10);
+//! set_priority(Interrupt::Spi, 1);
+//! ```
+//!
+//! The core specific interrupts (bank 3) could not be disabled at the interrupt controller in the same way. Their
+//! handlers are never preempted and they preempt the handlers of the GPU interrupts regardless of their priority. The
+//! pending core specific interrupts are therefore dispatched first before IRQ's are enabled for any GPU handler.
+//!
+//! A preempting interrupt overwrites the exception link register and the saved program status register (``ELR_EL1``
+//! and ``SPSR_EL1``) of the interrupt beeing handled. Both are saved before IRQ's are enabled and restored once the
+//! handler returns, so the exception vector calling ``__isr_default`` returns to the interrupted code. The exception
+//! vector need to handle IRQ's taken from the current exception level and need to save all general purpose registers
+//! on the stack. On 32Bit ARM IRQ's are not enabled while the handler is running, so the handlers are dispatched by
+//! their priority but never preempted.
+//!
+
+use core::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+
+use crate::{interface, Interrupt, CORE_BANK};
+
+/// The software priority of each interrupt indexed by the interrupt number
+static PRIORITIES: [AtomicU8; 128] = {
+  // the constant is only used as the initial value of the priority of each interrupt
+  #[allow(clippy::declare_interior_mutable_const)]
+  const LOWEST: AtomicU8 = AtomicU8::new(0);
+  [LOWEST; 128]
+};
+
+/// The GPU interrupts of the banks 0..2 currently disabled by the nesting. An interrupt deactivated while it is disabled
+/// by the nesting is removed, so it is not enabled again.
+static MASKED: [AtomicU32; 3] = [AtomicU32::new(0), AtomicU32::new(0), AtomicU32::new(0)];
+
+/// Set the software priority of the given interrupt. An interrupt with a higher priority preempts the handler of an
+/// interrupt with a lower priority. All interrupts have the lowest priority 0 by default.
+pub fn set_priority(irq: Interrupt, priority: u8) {
+  PRIORITIES[irq as usize].store(priority, Ordering::Relaxed);
+}
+
+/// Get the software priority of the given interrupt.
+pub fn priority(irq: Interrupt) -> u8 {
+  PRIORITIES[irq as usize].load(Ordering::Relaxed)
+}
+
+/// Dispatch the pending interrupts starting with the one of the highest priority. The core specific interrupts are
+/// dispatched first. They could not be disabled at the interrupt controller, so a preempting interrupt would dispatch
+/// them once more if they are still pending while a GPU handler is running. The GPU interrupts of lower or equal
+/// priority are disabled while the handler of a GPU interrupt is running with IRQ's enabled.
+pub(crate) fn dispatch(core: usize, mut pendings: [u32; 4]) {
+  let mut core_pendings = [0; 4];
+  core_pendings[CORE_BANK] = core::mem::take(&mut pendings[CORE_BANK]);
+  while let Some((bank, num)) = highest_pending(&core_pendings) {
+    core_pendings[bank] &= !(1 << num);
+    crate::dispatch_irq(core, bank, num);
+  }
+
+  while let Some((bank, num)) = highest_pending(&pendings) {
+    pendings[bank] &= !(1 << num);
+    let priority = PRIORITIES[(bank << 5) + num].load(Ordering::Relaxed);
+    let masked = interface::mask_gpu_irqs(lower_or_equal(priority));
+    for (masked, &bits) in MASKED.iter().zip(masked.iter()) {
+      masked.fetch_or(bits, Ordering::Relaxed);
+    }
+    // a preempting interrupt overwrites the exception state of the interrupt beeing handled
+    let state = interface::exception_state();
+    interface::enable_irq();
+    crate::dispatch_irq(core, bank, num);
+    interface::disable_irq();
+    interface::set_exception_state(state);
+
+    // enable the disabled interrupts again that have not been deactivated meanwhile
+    let mut unmask = [0; 3];
+    for (bank, unmask) in unmask.iter_mut().enumerate() {
+      *unmask = MASKED[bank].fetch_and(!masked[bank], Ordering::Relaxed) & masked[bank];
+    }
+    interface::unmask_gpu_irqs(unmask);
+  }
+}
+
+/// Remove the deactivated interrupt from the interrupts disabled by the nesting, so it is not enabled again once the
+/// preempted handler returns.
+pub(crate) fn forget(irq: Interrupt) {
+  let bank = (irq as usize) >> 5;
+  if bank < CORE_BANK {
+    MASKED[bank].fetch_and(!(1 << (irq as u32 & 0x1F)), Ordering::Relaxed);
+  }
+}
+
+/// Get the bank and the bit position of the pending interrupt with the highest priority. Of the interrupts with the
+/// same priority the one with the lowest number is returned.
+fn highest_pending(pendings: &[u32; 4]) -> Option<(usize, usize)> {
+  let mut highest: Option<(u8, usize, usize)> = None;
+  for (bank, &pending) in pendings.iter().enumerate() {
+    for num in crate::bitset::BitSet32(pending).iter() {
+      let priority = PRIORITIES[(bank << 5) + num as usize].load(Ordering::Relaxed);
+      let is_higher = match highest {
+        Some((highest, _, _)) => priority > highest,
+        None => true,
+      };
+      if is_higher {
+        highest = Some((priority, bank, num as usize));
+      }
+    }
+  }
+  highest.map(|(_, bank, num)| (bank, num))
+}
+
+/// Get the GPU interrupts of the banks 0..2 with a priority lower or equal to the given one
+fn lower_or_equal(priority: u8) -> [u32; 3] {
+  let mut mask = [0; 3];
+  for (bank, mask) in mask.iter_mut().enumerate() {
+    for num in 0..32 {
+      if PRIORITIES[(bank << 5) + num].load(Ordering::Relaxed) <= priority {
+        *mask |= 1 << num;
+      }
+    }
+  }
+  mask
+}
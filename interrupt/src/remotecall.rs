@@ -0,0 +1,147 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # Remote function calls
+//!
+//! A function could be executed on another core with [call_on_core] or on all cores with [call_on_all_cores], e.g. to
+//! flush the caches or to reconfigure the core timer of each core from one control core. The function is queued for
+//! the target core and the core is notified with an inter-processor interrupt in it's mailbox 3. The bit 31 of the
+//! message indicates the queued calls. They are executed while the ``Core<n>Mailbox3`` interrupt is dispatched before
+//! the remaining message bits are passed on to the channel of the interrupt.
+//!
+//! The target core need to have the interrupts enabled and the ``Core<n>Mailbox3`` interrupt must not be deactivated
+//! for the calls to be executed. The caller waits for the completion at most for the given number of ticks of the
+//! generic timer, as a core that does not handle interrupts never executes the call. For the same reason an interrupt
+//! handler must not wait for the completion of a remote call.
+//!
+//! ```no_run
+//! # use ruspiro_interrupt::*;
+//! // reconfigure something on core 2 and wait up to 1_000_000 ticks until it is done
+//! if call_on_core(2, || { /* implementation omitted */ }, Some(1_000_000)).is_err() {
+//!   // core 2 does not handle interrupts
+//! }
+//!
+//! // flush the caches of all cores without waiting
+//! call_on_all_cores(|| { /* implementation omitted */ }, None).unwrap();
+//! ```
+//!
+
+use crate::{interface, ipi, isrcell::IsrCell};
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// The bit of the mailbox 3 message indicating queued remote calls for the core
+pub(crate) const REMOTE_CALL: u32 = 1 << 31;
+
+/// The error returned if a remote call has not been executed on all target cores within the timeout. The call stays
+/// queued and is still executed once the core handles it's ``Core<n>Mailbox3`` interrupt.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct RemoteCallTimeout {
+  /// The number of cores that have not executed the function yet
+  pub pending: usize,
+}
+
+/// A function queued for a core together with the number of calls the caller is waiting for
+struct RemoteCall {
+  function: Box<dyn FnOnce() + Send>,
+  pending: Arc<AtomicUsize>,
+}
+
+/// The queue of remote calls of each core
+static CALL_QUEUES: [IsrCell<Vec<RemoteCall>>; 4] = [IsrCell::EMPTY; 4];
+
+/// Execute the given function on the given core. If the core is the current one the function is executed right away.
+/// Otherwise it is executed while the ``Core<n>Mailbox3`` interrupt is handled on the core. With ``wait`` beeing
+/// ``Some`` timeout in ticks of the generic timer this function returns once the function has been executed or the
+/// timeout has elapsed.
+///
+/// # Errors
+/// [RemoteCallTimeout] is returned if the function has not been executed within the timeout.
+///
+/// # Panics
+/// The function panics if the core is not in the range of 0..=3.
+pub fn call_on_core<F>(core: usize, function: F, wait: Option<u64>) -> Result<(), RemoteCallTimeout>
+where
+  F: FnOnce() + Send + 'static,
+{
+  assert!(core < 4, "core need to be in the range of 0..=3");
+  if core == interface::core_id() {
+    function();
+    return Ok(());
+  }
+  let pending = Arc::new(AtomicUsize::new(1));
+  queue(core, Box::new(function), pending.clone());
+  match wait {
+    Some(timeout) => wait_for(&pending, timeout),
+    None => Ok(()),
+  }
+}
+
+/// Execute the given function on all cores. It is executed on the current core right away and on the other cores
+/// while their ``Core<n>Mailbox3`` interrupt is handled. With ``wait`` beeing ``Some`` timeout in ticks of the generic
+/// timer this function returns once the function has been executed on all cores or the timeout has elapsed.
+///
+/// # Errors
+/// [RemoteCallTimeout] with the number of cores that have not executed the function is returned if it has not been
+/// executed on all cores within the timeout.
+pub fn call_on_all_cores<F>(function: F, wait: Option<u64>) -> Result<(), RemoteCallTimeout>
+where
+  F: Fn() + Send + Sync + 'static,
+{
+  let current = interface::core_id();
+  let function = Arc::new(function);
+  // each core signalled is counted before the call is queued, so a call executed right away is already accounted for
+  let pending = Arc::new(AtomicUsize::new(0));
+  for core in (0..4).filter(|&core| core != current) {
+    let function = function.clone();
+    pending.fetch_add(1, Ordering::AcqRel);
+    queue(core, Box::new(move || function()), pending.clone());
+  }
+  function();
+  match wait {
+    Some(timeout) => wait_for(&pending, timeout),
+    None => Ok(()),
+  }
+}
+
+/// Execute the remote calls queued for the given core
+pub(crate) fn execute(core: usize) {
+  if let Some(calls) = CALL_QUEUES[core].take() {
+    for call in calls {
+      (call.function)();
+      call.pending.fetch_sub(1, Ordering::Release);
+    }
+  }
+}
+
+/// Queue the function for the given core and notify the core about it
+fn queue(core: usize, function: Box<dyn FnOnce() + Send>, pending: Arc<AtomicUsize>) {
+  CALL_QUEUES[core].modify(|calls| {
+    calls
+      .get_or_insert_with(Vec::new)
+      .push(RemoteCall { function, pending })
+  });
+  ipi::notify_remote_call(core);
+}
+
+/// Wait until all calls the given counter belongs to have been executed or the timeout in ticks of the generic timer
+/// has elapsed
+fn wait_for(pending: &AtomicUsize, timeout: u64) -> Result<(), RemoteCallTimeout> {
+  let start = interface::counter();
+  loop {
+    // the calls are counted after the time is taken, so the calls executed until the timeout are not reported
+    let elapsed = interface::counter().wrapping_sub(start) >= timeout;
+    let count = pending.load(Ordering::Acquire);
+    if count == 0 {
+      return Ok(());
+    }
+    if elapsed {
+      return Err(RemoteCallTimeout { pending: count });
+    }
+    core::hint::spin_loop();
+  }
+}
@@ -0,0 +1,130 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # Shared interrupt lines
+//!
+//! Some interrupt lines are shared between several sources. The interrupt controller only indicates that the line is
+//! pending, the source raising the interrupt is indicated by a status register of the peripheral. A [SharedLine]
+//! describes such a line: the function reading the status register and the sources indicated by it. Each source has
+//! it's own handler implemented with ``#[IrqHandler(<line>, <source>)]`` and it's own channel. Only the sources that
+//! have been activated are passed their pending interrupt, the pending interrupt of any other source is reported as
+//! unhandled. A line like the one of a GPIO bank could also have a handler for the whole line that receives those
+//! interrupts instead.
+//!
+//! The peripheral may need to enable, disable or acknowledge the interrupt of a source, e.g. the event detection of a
+//! GPIO pin. The line describes how to disable and acknowledge the sources, so deactivating the line, e.g. as storm
+//! quarantine, disables all of it's sources.
+//!
+//! A new shared line is added by naming it's sources in the list of the macros crate, defining the line as static with
+//! the sources generated by ``ruspiro_interrupt_macros::shared_sources!(<line>)`` and adding the line to the
+//! [SHARED_LINES] registry. The entry of the interrupt in the interrupt list need to point to an ``extern "C"``
+//! function calling [SharedLine::dispatch].
+//!
+
+use super::{ErasedSender, IsrChannel};
+use crate::{bitset::BitSet32, isrcell::IsrCell, Interrupt};
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// A source of a shared interrupt line
+pub(crate) struct SharedSource {
+  /// The handler implemented for the source
+  pub(crate) handler: extern "C" fn(&IsrChannel),
+  /// The channel passed to the handler
+  pub(crate) channel: IsrCell<ErasedSender>,
+}
+
+/// An interrupt line shared between several sources
+pub(crate) struct SharedLine {
+  /// The interrupt of the line
+  pub(crate) irq: Interrupt,
+  /// Read the status register indicating the sources with a pending interrupt. Each source is indicated by the bit at
+  /// it's position within the sources.
+  pub(crate) status: fn() -> u32,
+  /// Acknowledge the interrupts of the given sources at the peripheral before their handlers are called. Without it
+  /// the handlers need to acknowledge the interrupt themselves.
+  pub(crate) acknowledge: Option<fn(u32)>,
+  /// Disable the interrupts of the given sources at the peripheral once they are deactivated
+  pub(crate) disable: Option<fn(u32)>,
+  /// The handler implemented for the whole line receiving the interrupts of the sources that have not been activated.
+  /// A line with such a handler could be activated as a whole with [activate](crate::activate).
+  pub(crate) line_handler: Option<extern "C" fn(&IsrChannel)>,
+  /// The sources sharing the line
+  pub(crate) sources: &'static [SharedSource],
+  /// The sources that have been activated indexed by their position within the sources
+  pub(crate) active: AtomicU32,
+}
+
+impl SharedLine {
+  /// Set the channel passed to the handler of the source with the given index and mark the source as activated
+  pub(crate) fn activate(&self, source: usize, channel: IsrChannel) {
+    self.sources[source].channel.replace(channel);
+    self.active.fetch_or(1 << source, Ordering::AcqRel);
+  }
+
+  /// Disable the source with the given index, release it's channel and mark it as deactivated. Returns ``true`` if no
+  /// other source of the line is activated any longer.
+  pub(crate) fn deactivate(&self, source: usize) -> bool {
+    if let Some(disable) = self.disable {
+      disable(1 << source);
+    }
+    self.sources[source].channel.take();
+    self.active.fetch_and(!(1 << source), Ordering::AcqRel) & !(1 << source) == 0
+  }
+
+  /// Disable all activated sources, release their channels and mark them as deactivated
+  pub(crate) fn release(&self) {
+    let active = self.active.swap(0, Ordering::AcqRel);
+    if let Some(disable) = self.disable {
+      disable(active);
+    }
+    for source in self.sources {
+      source.channel.take();
+    }
+  }
+
+  /// Call the handler of each activated source indicating a pending interrupt in the status register. A source that
+  /// has not been activated is not passed it's interrupt, even if it is pending while another source is active. The
+  /// handler of the whole line is called with the channel of the line instead, without such handler the interrupt line
+  /// is reported as unhandled.
+  pub(crate) fn dispatch(&self, channel: &IsrChannel) {
+    let status = (self.status)();
+    let active = self.active.load(Ordering::Acquire);
+    let pending = status & active;
+    // acknowledge the interrupts before calling the handler, so an interrupt raised while they are running is not lost
+    if let Some(acknowledge) = self.acknowledge {
+      if pending != 0 {
+        acknowledge(pending);
+      }
+    }
+    for source in BitSet32(pending).iter() {
+      let source = &self.sources[source as usize];
+      source.channel.lend(|channel| (source.handler)(channel));
+    }
+
+    if status & !active != 0 {
+      match self.line_handler {
+        Some(handler) => handler(channel),
+        None => crate::report_unhandled(self.irq as u32),
+      }
+    }
+  }
+}
+
+/// The registry of the shared interrupt lines that require the activation of their sources
+static SHARED_LINES: [&SharedLine; 6] = [
+  &crate::auxhandler::AUX_LINE,
+  &crate::dmahandler::DMA7_8_LINE,
+  &crate::dmahandler::DMA9_10_LINE,
+  &crate::gpiohandler::GPIO_BANK0_LINE,
+  &crate::gpiohandler::GPIO_BANK1_LINE,
+  &crate::gpiohandler::GPIO_BANK2_LINE,
+];
+
+/// Get the shared line of the given interrupt, if it is one
+pub(crate) fn shared_line(irq: Interrupt) -> Option<&'static SharedLine> {
+  SHARED_LINES.iter().copied().find(|line| line.irq == irq)
+}
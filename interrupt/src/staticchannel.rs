@@ -0,0 +1,274 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # Static ISR channel
+//!
+//! A fixed capacity channel that does not require any heap allocation to send data from an interrupt handler to the
+//! normal processing. The channel is a lock-free ring buffer that allows multiple producers (e.g. the interrupt
+//! handler running on different cores) and multiple consumers. Besides the receiver a sender consumes the oldest data
+//! as well if the channel is full and the [OverflowPolicy::DropOldest] is applied. As there is no allocator involved
+//! the storage of the channel need to be provided as a ``static``:
+//!
+//! ```no_run
+//! # use ruspiro_interrupt::*;
+//! static UART_CHANNEL: StaticIsrChannel<u8, 64> = StaticIsrChannel::new(OverflowPolicy::DropOldest);
+//!
+//! #[IrqHandler(Pl011)]
+//! fn uart_handler(channel: Option<StaticIsrSender<u8, 64>>) {
+//!   if let Some(tx) = channel {
+//!     tx.send(0x55);
+//!   }
+//! }
+//!
+//! fn main() {
+//!   let (tx, rx) = static_isr_channel(&UART_CHANNEL);
+//!   activate(Interrupt::Pl011, tx);
+//!   while let Some(data) = rx.recv() {
+//!     // process the received data
+//!   }
+//! }
+//! ```
+//!
+//! The channel implements a bounded queue with a sequence number per slot. A sender claims the next free slot by
+//! advancing the enqueue position and publishes the data by updating the slot sequence. The receiver takes the data
+//! once the slot sequence indicates it has been published. Like the senders the consumers claim the data by advancing
+//! the dequeue position, so the receiver and a sender dropping the oldest data never take the same slot.
+//!
+
+use core::{
+  any::Any,
+  cell::UnsafeCell,
+  mem::MaybeUninit,
+  sync::atomic::{AtomicUsize, Ordering},
+};
+
+use super::{ErasedSender, FromIsrChannel, IntoIsrChannel, IsrChannel};
+
+/// The policy applied when sending data to a channel that is full
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum OverflowPolicy {
+  /// The data that shall be send is dropped
+  DropNewest,
+  /// The oldest data in the channel is dropped to make room for the data that shall be send
+  DropOldest,
+  /// The data that shall be send is dropped and the number of dropped items is counted
+  CountAndDrop,
+}
+
+/// A single entry of the ring buffer. The sequence is stored relative to the index of the slot, so all slots could be
+/// initialized with the same value in a ``const`` context.
+struct Slot<T> {
+  sequence: AtomicUsize,
+  value: UnsafeCell<MaybeUninit<T>>,
+}
+
+impl<T> Slot<T> {
+  // the constant is only used as the initial value of each slot of a new channel
+  #[allow(clippy::declare_interior_mutable_const)]
+  const EMPTY: Self = Self {
+    sequence: AtomicUsize::new(0),
+    value: UnsafeCell::new(MaybeUninit::uninit()),
+  };
+}
+
+/// The storage of a statically sized ISR channel with the capacity of ``N`` items
+pub struct StaticIsrChannel<T, const N: usize> {
+  slots: [Slot<T>; N],
+  enqueue_pos: AtomicUsize,
+  dequeue_pos: AtomicUsize,
+  dropped: AtomicUsize,
+  policy: OverflowPolicy,
+}
+
+unsafe impl<T: Send, const N: usize> Sync for StaticIsrChannel<T, N> {}
+
+impl<T, const N: usize> StaticIsrChannel<T, N> {
+  /// Evaluated with the creation of each channel to reject a channel without any capacity at compile time. A panic
+  /// within a constant like ``assert!(N > 0)`` is not available with the toolchain this crate is build with. So the
+  /// evaluation indexes a single element array out of bounds if ``N`` is 0 which fails the compilation the same way.
+  const CAPACITY_CHECK: () = [()][(N == 0) as usize];
+
+  /// Create a new channel applying the given [OverflowPolicy] if data is send while the channel is full. The capacity
+  /// ``N`` need to be at least 1, a channel without capacity does not compile:
+  ///
+  /// ```compile_fail
+  /// # use ruspiro_interrupt::*;
+  /// static CHANNEL: StaticIsrChannel<u8, 0> = StaticIsrChannel::new(OverflowPolicy::DropNewest);
+  /// ```
+  pub const fn new(policy: OverflowPolicy) -> Self {
+    // the constant is only evaluated if it is used
+    #[allow(clippy::let_unit_value)]
+    let _ = Self::CAPACITY_CHECK;
+    Self {
+      slots: [Slot::EMPTY; N],
+      enqueue_pos: AtomicUsize::new(0),
+      dequeue_pos: AtomicUsize::new(0),
+      dropped: AtomicUsize::new(0),
+      policy,
+    }
+  }
+
+  /// Get the sequence of the slot at the given position
+  fn sequence(&self, pos: usize) -> usize {
+    self.slots[pos % N]
+      .sequence
+      .load(Ordering::Acquire)
+      .wrapping_add(pos % N)
+  }
+
+  /// Update the sequence of the slot at the given position
+  fn set_sequence(&self, pos: usize, sequence: usize) {
+    self.slots[pos % N]
+      .sequence
+      .store(sequence.wrapping_sub(pos % N), Ordering::Release);
+  }
+
+  /// Put the data into the channel. The data is returned if the channel is full.
+  fn push(&self, value: T) -> Result<(), T> {
+    let mut pos = self.enqueue_pos.load(Ordering::Relaxed);
+    loop {
+      let diff = self.sequence(pos).wrapping_sub(pos) as isize;
+      if diff == 0 {
+        match self.enqueue_pos.compare_exchange_weak(
+          pos,
+          pos.wrapping_add(1),
+          Ordering::Relaxed,
+          Ordering::Relaxed,
+        ) {
+          Ok(_) => {
+            unsafe { (*self.slots[pos % N].value.get()).as_mut_ptr().write(value) };
+            self.set_sequence(pos, pos.wrapping_add(1));
+            return Ok(());
+          }
+          Err(current) => pos = current,
+        }
+      } else if diff < 0 {
+        return Err(value);
+      } else {
+        pos = self.enqueue_pos.load(Ordering::Relaxed);
+      }
+    }
+  }
+
+  /// Take the oldest data from the channel if there is any
+  fn pop(&self) -> Option<T> {
+    let mut pos = self.dequeue_pos.load(Ordering::Relaxed);
+    loop {
+      let diff = self.sequence(pos).wrapping_sub(pos.wrapping_add(1)) as isize;
+      if diff == 0 {
+        match self.dequeue_pos.compare_exchange_weak(
+          pos,
+          pos.wrapping_add(1),
+          Ordering::Relaxed,
+          Ordering::Relaxed,
+        ) {
+          Ok(_) => {
+            let value = unsafe { (*self.slots[pos % N].value.get()).as_ptr().read() };
+            self.set_sequence(pos, pos.wrapping_add(N));
+            return Some(value);
+          }
+          Err(current) => pos = current,
+        }
+      } else if diff < 0 {
+        return None;
+      } else {
+        pos = self.dequeue_pos.load(Ordering::Relaxed);
+      }
+    }
+  }
+
+  /// Send data through the channel applying the [OverflowPolicy] if the channel is full
+  fn send(&self, value: T) {
+    if let Err(rejected) = self.push(value) {
+      match self.policy {
+        OverflowPolicy::DropNewest => (),
+        OverflowPolicy::CountAndDrop => {
+          self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        OverflowPolicy::DropOldest => {
+          // make room for the new data and try once more. If other senders have taken the free slot in the meantime
+          // the data is dropped instead of competing with them for an unbounded time
+          self.pop();
+          if self.push(rejected).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+          }
+        }
+      }
+    }
+  }
+}
+
+impl<T, const N: usize> Drop for StaticIsrChannel<T, N> {
+  fn drop(&mut self) {
+    while self.pop().is_some() {}
+  }
+}
+
+/// The sending side of a [StaticIsrChannel]. It can be passed to the activation of an interrupt like the [IsrSender]
+/// and is received by the interrupt handler with the parameter type ``Option<StaticIsrSender<T, N>>``.
+///
+/// [IsrSender]: super::IsrSender
+pub struct StaticIsrSender<T: 'static, const N: usize>(&'static StaticIsrChannel<T, N>);
+
+impl<T, const N: usize> StaticIsrSender<T, N> {
+  /// Send data through the channel. This never blocks nor allocates. If the channel is full the [OverflowPolicy] of the
+  /// channel is applied.
+  pub fn send(&self, value: T) {
+    self.0.send(value);
+  }
+}
+
+impl<T, const N: usize> Clone for StaticIsrSender<T, N> {
+  fn clone(&self) -> Self {
+    *self
+  }
+}
+
+impl<T, const N: usize> Copy for StaticIsrSender<T, N> {}
+
+/// The sender only keeps the reference to the static channel, so activating an interrupt with it does not allocate
+impl<T: 'static, const N: usize> IntoIsrChannel for StaticIsrSender<T, N> {
+  fn into_isr_channel(self) -> IsrChannel {
+    Some(ErasedSender::Static(self.0))
+  }
+}
+
+impl<T: 'static, const N: usize> FromIsrChannel for StaticIsrSender<T, N> {
+  fn from_isr_channel(channel: &ErasedSender) -> Option<Self> {
+    match *channel {
+      ErasedSender::Static(channel) => {
+        let channel: &'static dyn Any = channel;
+        channel.downcast_ref().map(StaticIsrSender)
+      }
+      ErasedSender::Boxed(_) => None,
+    }
+  }
+}
+
+/// The receiving side of a [StaticIsrChannel]
+pub struct StaticIsrReceiver<T: 'static, const N: usize>(&'static StaticIsrChannel<T, N>);
+
+impl<T, const N: usize> StaticIsrReceiver<T, N> {
+  /// Receive the oldest data send through the channel. This does not block and returns ``None`` if the channel is
+  /// empty.
+  pub fn recv(&self) -> Option<T> {
+    self.0.pop()
+  }
+
+  /// The number of items dropped because the channel was full. This is counted with the [OverflowPolicy::CountAndDrop]
+  /// and with the [OverflowPolicy::DropOldest] if the data could still not be send after dropping the oldest one.
+  pub fn dropped(&self) -> usize {
+    self.0.dropped.load(Ordering::Relaxed)
+  }
+}
+
+/// Get the sender and the receiver of the given static channel. There should only be one receiver for each channel.
+pub fn static_isr_channel<T, const N: usize>(
+  channel: &'static StaticIsrChannel<T, N>,
+) -> (StaticIsrSender<T, N>, StaticIsrReceiver<T, N>) {
+  (StaticIsrSender(channel), StaticIsrReceiver(channel))
+}
@@ -0,0 +1,106 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # Interrupt statistics
+//!
+//! With the `stats` feature the dispatching of the interrupts maintains counters for each interrupt. They tell how
+//! often the handler of an interrupt has been called, when this happened the last time and how long the handler took.
+//! The time is measured in ticks of the physical count of the generic timer (``CNTPCT_EL0``).
+//!
+//! ```no_run
+//! # use ruspiro_interrupt::*;
+//! let timer = stats(Interrupt::ArmTimer);
+//! if timer.count == 0 {
+//!   // the handler of the ArmTimer has never been called
+//! }
+//!
+//! for (irq, stats) in stats_iter().filter(|(_, stats)| stats.count != 0) {
+//!   // report the interrupts that have fired
+//! }
+//! ```
+//!
+
+use core::{
+  convert::TryFrom,
+  sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::{interface, Interrupt};
+
+/// The statistics of an interrupt. The timestamp and the durations are given in ticks of the physical count of the
+/// generic timer.
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+pub struct IrqStats {
+  /// The number of times the handler of the interrupt has been called
+  pub count: u64,
+  /// The physical count of the generic timer when the handler has been called the last time
+  pub last_fired: u64,
+  /// The longest duration of a single handler call
+  pub max_duration: u64,
+  /// The average duration of the handler calls
+  pub avg_duration: u64,
+}
+
+/// The counters maintained for each interrupt while it is dispatched
+struct IrqCounters {
+  count: AtomicU64,
+  last_fired: AtomicU64,
+  max_duration: AtomicU64,
+  total_duration: AtomicU64,
+}
+
+impl IrqCounters {
+  // the constant is only used as the initial value of the counters of each interrupt
+  #[allow(clippy::declare_interior_mutable_const)]
+  const NEW: Self = Self {
+    count: AtomicU64::new(0),
+    last_fired: AtomicU64::new(0),
+    max_duration: AtomicU64::new(0),
+    total_duration: AtomicU64::new(0),
+  };
+}
+
+/// The counters of all interrupts indexed by the interrupt number
+static IRQ_STATS: [IrqCounters; 128] = [IrqCounters::NEW; 128];
+
+/// Get the statistics of the given interrupt.
+pub fn stats(irq: Interrupt) -> IrqStats {
+  let counters = &IRQ_STATS[irq as usize];
+  let count = counters.count.load(Ordering::Relaxed);
+  let total_duration = counters.total_duration.load(Ordering::Relaxed);
+  IrqStats {
+    count,
+    last_fired: counters.last_fired.load(Ordering::Relaxed),
+    max_duration: counters.max_duration.load(Ordering::Relaxed),
+    avg_duration: total_duration.checked_div(count).unwrap_or(0),
+  }
+}
+
+/// Iterate over the statistics of all interrupts a handler could be implemented for.
+pub fn stats_iter() -> impl Iterator<Item = (Interrupt, IrqStats)> {
+  (0..IRQ_STATS.len() as u8)
+    .filter_map(|num| Interrupt::try_from(num).ok())
+    .map(|irq| (irq, stats(irq)))
+}
+
+/// Get the timestamp the dispatching of an interrupt to it's handler starts at
+pub(crate) fn start() -> u64 {
+  interface::counter()
+}
+
+/// Record the call of the handler of the interrupt at the given bank and bit position that started at the given
+/// timestamp
+pub(crate) fn record(bank: usize, num: usize, start: u64) {
+  let duration = interface::counter().wrapping_sub(start);
+  let counters = &IRQ_STATS[(bank << 5) + num];
+  counters.count.fetch_add(1, Ordering::Relaxed);
+  counters.last_fired.store(start, Ordering::Relaxed);
+  counters.max_duration.fetch_max(duration, Ordering::Relaxed);
+  counters
+    .total_duration
+    .fetch_add(duration, Ordering::Relaxed);
+}
@@ -0,0 +1,152 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # Interrupt storm detection
+//!
+//! An interrupt that is not acknowledged by it's handler is raised again immediately after the handler returns. This
+//! endless interrupt loop hangs the core without any chance to report it. The storm detection counts how often each
+//! interrupt is dispatched within a time window. If an interrupt is dispatched more often than the configured
+//! threshold it is deactivated and put into quarantine. The handler registered with [set_storm_handler] is notified
+//! about this, so the application could report the storm or try to recover the device.
+//!
+//! ```no_run
+//! # use ruspiro_interrupt::*;
+//! // quarantine an interrupt raised more than 1000 times within 1_000_000 ticks of the generic timer
+//! configure_storm_detection(1000, 1_000_000);
+//! set_storm_handler(Box::new(|irq| {
+//!   // report the interrupt storm
+//! }));
+//! ```
+//!
+//! The storm detection is disabled by default. A quarantined interrupt is released once it is activated again.
+//!
+
+use alloc::boxed::Box;
+use core::{
+  convert::TryFrom,
+  sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+};
+
+use crate::{interface, isrcell::IsrCell, Interrupt, RoutingMode};
+
+/// The handler notified about an interrupt that has been put into quarantine
+type StormHandler = Box<dyn FnMut(Interrupt) + Send>;
+
+/// The number of dispatches within the time window an interrupt is quarantined after. 0 disables the storm detection.
+static THRESHOLD: AtomicU32 = AtomicU32::new(0);
+/// The time window in ticks of the generic timer the dispatches of an interrupt are counted in
+static WINDOW: AtomicU64 = AtomicU64::new(0);
+
+/// The dispatches of an interrupt within the current time window
+struct StormCounter {
+  window_start: AtomicU64,
+  count: AtomicU32,
+  quarantined: AtomicBool,
+}
+
+impl StormCounter {
+  // the constant is only used as the initial value of the counter of each interrupt
+  #[allow(clippy::declare_interior_mutable_const)]
+  const NEW: Self = Self {
+    window_start: AtomicU64::new(0),
+    count: AtomicU32::new(0),
+    quarantined: AtomicBool::new(false),
+  };
+}
+
+/// The storm counters of all interrupts indexed by the interrupt number
+static STORM_COUNTERS: [StormCounter; 128] = [StormCounter::NEW; 128];
+
+static STORM_HANDLER: IsrCell<StormHandler> = IsrCell::new();
+
+/// Enable the storm detection. An interrupt that is dispatched more than ``threshold`` times within ``window`` ticks of
+/// the physical count of the generic timer is deactivated and put into quarantine. A ``threshold`` of 0 disables the
+/// storm detection.
+pub fn configure_storm_detection(threshold: u32, window: u64) {
+  WINDOW.store(window, Ordering::Relaxed);
+  THRESHOLD.store(threshold, Ordering::Release);
+}
+
+/// Disable the storm detection. Interrupts already in quarantine stay deactivated.
+pub fn disable_storm_detection() {
+  THRESHOLD.store(0, Ordering::Release);
+}
+
+/// Register the handler that is notified with the interrupt that has been put into quarantine. It is called from the
+/// interrupt handling of the interrupt causing the storm. Registering a handler replaces the previous one.
+pub fn set_storm_handler(handler: Box<dyn FnMut(Interrupt) + Send>) {
+  STORM_HANDLER.replace(Some(handler));
+}
+
+/// Remove the handler registered with [set_storm_handler].
+pub fn clear_storm_handler() {
+  STORM_HANDLER.take();
+}
+
+/// Check whether the given interrupt has been put into quarantine because of an interrupt storm.
+pub fn is_quarantined(irq: Interrupt) -> bool {
+  STORM_COUNTERS[irq as usize]
+    .quarantined
+    .load(Ordering::Acquire)
+}
+
+/// Release the given interrupt from quarantine and restart counting it's dispatches. This is called with the activation
+/// of the interrupt.
+pub(crate) fn release(irq: Interrupt) {
+  let counter = &STORM_COUNTERS[irq as usize];
+  counter.count.store(0, Ordering::Relaxed);
+  counter.quarantined.store(false, Ordering::Release);
+}
+
+/// Count the dispatch of the interrupt at the given bank and bit position on the given core. If this is a storm the
+/// interrupt is deactivated, put into quarantine and ``true`` is returned. The handler of the interrupt must not be
+/// called in this case.
+pub(crate) fn check(bank: usize, num: usize, core: usize, mode: RoutingMode) -> bool {
+  let threshold = THRESHOLD.load(Ordering::Acquire);
+  if threshold == 0 {
+    return false;
+  }
+  let irq = match Interrupt::try_from(((bank << 5) + num) as u8) {
+    Ok(irq) => irq,
+    Err(_) => return false,
+  };
+
+  let counter = &STORM_COUNTERS[irq as usize];
+  let now = interface::counter();
+  let window_start = counter.window_start.load(Ordering::Relaxed);
+  if counter.count.load(Ordering::Relaxed) == 0
+    || now.wrapping_sub(window_start) > WINDOW.load(Ordering::Relaxed)
+  {
+    // start a new time window with this dispatch
+    counter.window_start.store(now, Ordering::Relaxed);
+    counter.count.store(1, Ordering::Relaxed);
+    return false;
+  }
+  if counter.count.fetch_add(1, Ordering::Relaxed) < threshold {
+    return false;
+  }
+
+  quarantine(irq, core, mode);
+  counter.quarantined.store(true, Ordering::Release);
+  STORM_HANDLER.lend_mut(|handler| {
+    if let Some(handler) = handler {
+      handler(irq);
+    }
+  });
+  true
+}
+
+/// Deactivate the interrupt causing the storm the same way it has been activated
+fn quarantine(irq: Interrupt, core: usize, mode: RoutingMode) {
+  match mode {
+    RoutingMode::Fiq => crate::deactivate_fiq(irq),
+    RoutingMode::Irq if crate::core_local_slot(irq).is_some() => {
+      crate::deactivate_on_core(irq, core)
+    }
+    RoutingMode::Irq => crate::deactivate(irq),
+  }
+}
@@ -0,0 +1,83 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # Common test fixture
+//!
+//! The simulated interrupt controller and the handler tables are shared between all tests of a test binary, so the
+//! tests use this fixture to run one after the other. It also provides the entry into the interrupt handling like the
+//! exception vector would do.
+//!
+
+#![allow(dead_code)]
+
+use core::sync::atomic::{AtomicBool, Ordering};
+use ruspiro_interrupt::*;
+
+extern "C" {
+  fn __isr_default();
+  fn __fiq_default();
+}
+
+static SERIAL: AtomicBool = AtomicBool::new(false);
+
+/// Releases the serialization of the tests once the test has finished, even if it panics
+pub struct SerialGuard;
+
+impl Drop for SerialGuard {
+  fn drop(&mut self) {
+    SERIAL.store(false, Ordering::Release);
+  }
+}
+
+/// Serialize the tests and start each one with a freshly reset simulation
+pub fn serialize() -> SerialGuard {
+  while SERIAL
+    .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+    .is_err()
+  {
+    std::thread::yield_now();
+  }
+  sim::reset();
+  SerialGuard
+}
+
+/// Serialize the tests and start each one with a freshly initialized interrupt controller
+pub fn setup() -> SerialGuard {
+  let guard = serialize();
+  initialize();
+  guard
+}
+
+/// Enter the interrupt handling like the IRQ exception vector would do
+pub fn dispatch_irq() {
+  unsafe { __isr_default() };
+}
+
+/// Enter the fast interrupt handling like the FIQ exception vector would do
+pub fn dispatch_fiq() {
+  unsafe { __fiq_default() };
+}
+
+/// Enter the interrupt handling on the given core
+pub fn dispatch_irq_on_core(core: usize) {
+  sim::set_core(core);
+  dispatch_irq();
+  sim::set_core(0);
+}
+
+/// Enter the fast interrupt handling on the given core
+pub fn dispatch_fiq_on_core(core: usize) {
+  sim::set_core(core);
+  dispatch_fiq();
+  sim::set_core(0);
+}
+
+/// Get the sender of a new channel. The receiver is dropped, so the sender only serves the activation.
+pub fn channel<T>() -> IsrSender<T> {
+  let (tx, _) = isr_channel::<T>();
+  tx
+}
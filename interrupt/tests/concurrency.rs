@@ -0,0 +1,187 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # Concurrent activation tests
+//!
+//! The tests of activating and deactivating interrupts while they are dispatched, from within their handler and from
+//! another core. They need to be executed on the host with e.g.
+//! ``cargo test --features pi3,sim --target x86_64-unknown-linux-gnu``.
+//!
+
+#![cfg(feature = "sim")]
+
+mod common;
+
+use common::*;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use ruspiro_interrupt::*;
+
+static SYSTEM_TIMER_CALLS: AtomicUsize = AtomicUsize::new(0);
+static SYSTEM_TIMER_CHANNEL: AtomicBool = AtomicBool::new(false);
+/// Whether the handler of the system timer shall deactivate it's interrupt
+static SYSTEM_TIMER_DEACTIVATE: AtomicBool = AtomicBool::new(false);
+static PL011_CALLS: AtomicUsize = AtomicUsize::new(0);
+/// The calls of the SPI handler that received the channel
+static SPI_CHANNELS: AtomicUsize = AtomicUsize::new(0);
+/// Whether the handler of the SPI interrupt shall dispatch it on core 1 while it is running on core 0
+static SPI_DISPATCH_ON_CORE1: AtomicBool = AtomicBool::new(false);
+
+#[IrqHandler(SystemTimer3)]
+fn system_timer_handler(channel: Option<IsrSender<u8>>) {
+  SYSTEM_TIMER_CALLS.fetch_add(1, Ordering::SeqCst);
+  SYSTEM_TIMER_CHANNEL.store(channel.is_some(), Ordering::SeqCst);
+  if SYSTEM_TIMER_DEACTIVATE.load(Ordering::SeqCst) {
+    deactivate(Interrupt::SystemTimer3);
+  }
+  sim::clear(Interrupt::SystemTimer3);
+}
+
+#[IrqHandler(Pl011)]
+fn pl011_handler(_channel: Option<IsrSender<u8>>) {
+  PL011_CALLS.fetch_add(1, Ordering::SeqCst);
+}
+
+#[IrqHandler(Spi)]
+fn spi_handler(channel: Option<IsrSender<u8>>) {
+  if channel.is_some() {
+    SPI_CHANNELS.fetch_add(1, Ordering::SeqCst);
+  }
+  // the interrupt is raised on core 1 as well before it is acknowledged here
+  if SPI_DISPATCH_ON_CORE1.swap(false, Ordering::SeqCst) {
+    route_gpu_irqs(1, RoutingMode::Irq);
+    dispatch_irq_on_core(1);
+  }
+  sim::clear(Interrupt::Spi);
+}
+
+/// Records whether it has been dropped
+struct DropFlag(std::sync::Arc<AtomicBool>);
+
+impl Drop for DropFlag {
+  fn drop(&mut self) {
+    self.0.store(true, Ordering::SeqCst);
+  }
+}
+
+#[test]
+fn handler_deactivates_it_own_interrupt() {
+  let _guard = setup();
+  let calls = SYSTEM_TIMER_CALLS.load(Ordering::SeqCst);
+  SYSTEM_TIMER_DEACTIVATE.store(true, Ordering::SeqCst);
+
+  activate(Interrupt::SystemTimer3, channel::<u8>());
+  sim::raise(Interrupt::SystemTimer3);
+  dispatch_irq();
+
+  assert_eq!(SYSTEM_TIMER_CALLS.load(Ordering::SeqCst), calls + 1);
+  assert!(SYSTEM_TIMER_CHANNEL.load(Ordering::SeqCst));
+  assert!(!sim::is_active(Interrupt::SystemTimer3));
+
+  // the channel released by the handler is not put back once it returns
+  SYSTEM_TIMER_DEACTIVATE.store(false, Ordering::SeqCst);
+  activate(Interrupt::SystemTimer3, None);
+  sim::raise(Interrupt::SystemTimer3);
+  dispatch_irq();
+
+  assert_eq!(SYSTEM_TIMER_CALLS.load(Ordering::SeqCst), calls + 2);
+  assert!(!SYSTEM_TIMER_CHANNEL.load(Ordering::SeqCst));
+
+  deactivate(Interrupt::SystemTimer3);
+}
+
+#[test]
+fn registered_handler_unregisters_itself() {
+  let _guard = setup();
+  let calls = std::sync::Arc::new(AtomicUsize::new(0));
+
+  let handler_calls = calls.clone();
+  register_handler(
+    Interrupt::Usb,
+    Box::new(move || {
+      handler_calls.fetch_add(1, Ordering::SeqCst);
+      unregister_handler(Interrupt::Usb);
+    }),
+  );
+  activate_fiq(Interrupt::Usb, None);
+  sim::raise(Interrupt::Usb);
+  dispatch_fiq();
+  dispatch_fiq();
+
+  assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+  deactivate_fiq(Interrupt::Usb);
+}
+
+#[test]
+fn activate_while_dispatching_on_another_core() {
+  let _guard = setup();
+
+  activate(Interrupt::Pl011, channel::<u8>());
+  sim::raise(Interrupt::Pl011);
+  let activation = std::thread::spawn(|| {
+    for _ in 0..1000 {
+      deactivate(Interrupt::Pl011);
+      activate(Interrupt::Pl011, channel::<u8>());
+    }
+  });
+  for _ in 0..1000 {
+    dispatch_irq();
+  }
+  activation.join().unwrap();
+
+  // the handler never acknowledges the interrupt, so it is called with each dispatch once the activation settled
+  assert!(sim::is_active(Interrupt::Pl011));
+  let calls = PL011_CALLS.load(Ordering::SeqCst);
+  dispatch_irq();
+  assert_eq!(PL011_CALLS.load(Ordering::SeqCst), calls + 1);
+
+  deactivate(Interrupt::Pl011);
+}
+
+// with the software interrupt nesting the interrupt is disabled while it's handler is running
+#[cfg(not(feature = "nesting"))]
+#[test]
+fn channel_is_passed_to_the_dispatch_on_another_core() {
+  let _guard = setup();
+  SPI_CHANNELS.store(0, Ordering::SeqCst);
+  SPI_DISPATCH_ON_CORE1.store(true, Ordering::SeqCst);
+
+  activate(Interrupt::Spi, channel::<u8>());
+  sim::raise(Interrupt::Spi);
+  dispatch_irq();
+
+  // the handler running on core 0 and the one running on core 1 meanwhile received the channel
+  assert_eq!(SPI_CHANNELS.load(Ordering::SeqCst), 2);
+
+  deactivate(Interrupt::Spi);
+}
+
+#[test]
+fn handler_unregistered_by_itself_is_not_dropped_while_dispatching() {
+  let _guard = setup();
+  let dropped = std::sync::Arc::new(AtomicBool::new(false));
+
+  let flag = DropFlag(dropped.clone());
+  register_handler(
+    Interrupt::I2c,
+    Box::new(move || {
+      let _ = &flag;
+      unregister_handler(Interrupt::I2c);
+      sim::clear(Interrupt::I2c);
+    }),
+  );
+  activate(Interrupt::I2c, None);
+  sim::raise(Interrupt::I2c);
+  dispatch_irq();
+  assert!(!dropped.load(Ordering::SeqCst));
+
+  // the next registration outside of the interrupt handling drops the handler
+  unregister_handler(Interrupt::I2c);
+  assert!(dropped.load(Ordering::SeqCst));
+
+  deactivate(Interrupt::I2c);
+}
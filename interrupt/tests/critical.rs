@@ -0,0 +1,77 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # Critical section tests
+//!
+//! The tests of the critical sections masking the interrupts. They need to be executed on the host with e.g.
+//! ``cargo test --features pi3,sim --target x86_64-unknown-linux-gnu``.
+//!
+
+#![cfg(feature = "sim")]
+
+mod common;
+
+use common::*;
+use ruspiro_interrupt::*;
+
+#[test]
+fn enable_and_disable_interrupts() {
+  let _guard = serialize();
+  assert!(!are_interrupts_enabled());
+
+  enable_interrupts();
+  assert!(are_interrupts_enabled());
+  assert!(is_irq_enabled());
+  assert!(is_fiq_enabled());
+
+  disable_interrupts();
+  assert!(!is_irq_enabled());
+  assert!(!is_fiq_enabled());
+}
+
+#[test]
+fn nested_critical_sections_restore_the_state() {
+  let _guard = serialize();
+  enable_interrupts();
+
+  let value = critical_section(|_| {
+    assert!(!are_interrupts_enabled());
+    critical_section(|_| assert!(!are_interrupts_enabled()));
+    // leaving the inner critical section keeps the interrupts masked
+    assert!(!are_interrupts_enabled());
+    42
+  });
+
+  assert_eq!(value, 42);
+  assert!(are_interrupts_enabled());
+}
+
+#[test]
+fn guard_keeps_masked_interrupts_masked() {
+  let _guard = serialize();
+  assert!(!are_interrupts_enabled());
+
+  {
+    let _irq_guard = InterruptGuard::new();
+    assert!(!are_interrupts_enabled());
+  }
+  assert!(!are_interrupts_enabled());
+}
+
+#[test]
+fn guard_restores_the_state_on_drop() {
+  let _guard = serialize();
+  enable_interrupts();
+
+  let irq_guard = InterruptGuard::new();
+  assert!(!is_irq_enabled());
+  assert!(!is_fiq_enabled());
+  drop(irq_guard);
+
+  assert!(is_irq_enabled());
+  assert!(is_fiq_enabled());
+}
@@ -0,0 +1,58 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # critical-section implementation tests
+//!
+//! The tests of the implementation of the `critical-section` crate. They need to be executed on the host with e.g.
+//! ``cargo test --features pi3,sim,critical-section-impl --target x86_64-unknown-linux-gnu``.
+//!
+
+#![cfg(all(feature = "sim", feature = "critical-section-impl"))]
+
+mod common;
+
+use common::*;
+use ruspiro_interrupt::*;
+
+#[test]
+fn mask_interrupts_inside_the_critical_section() {
+  let _guard = serialize();
+  enable_interrupts();
+
+  critical_section::with(|_| {
+    assert!(!are_interrupts_enabled());
+    // a nested critical section on the same core does not wait for the lock it already holds
+    critical_section::with(|_| assert!(!are_interrupts_enabled()));
+    assert!(!are_interrupts_enabled());
+  });
+
+  assert!(are_interrupts_enabled());
+}
+
+#[test]
+fn nest_with_the_interrupt_guard() {
+  let _guard = serialize();
+  enable_interrupts();
+
+  critical_section(|_| {
+    critical_section::with(|_| assert!(!are_interrupts_enabled()));
+    assert!(!are_interrupts_enabled());
+  });
+
+  assert!(are_interrupts_enabled());
+}
+
+#[test]
+fn release_the_lock_for_other_cores() {
+  let _guard = serialize();
+
+  critical_section::with(|_| {});
+  sim::set_core(1);
+  critical_section::with(|_| {});
+  sim::set_core(0);
+  critical_section::with(|_| {});
+}
@@ -0,0 +1,130 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # DMA interrupt tests
+//!
+//! The tests of dispatching the DMA channel interrupts including the interrupt lines shared between two channels. They
+//! need to be executed on the host with e.g. ``cargo test --features pi3,sim --target x86_64-unknown-linux-gnu``.
+//!
+
+#![cfg(feature = "sim")]
+
+mod common;
+
+use common::*;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use ruspiro_interrupt::*;
+
+static DMA3_CALLS: AtomicUsize = AtomicUsize::new(0);
+static DMA7_CALLS: AtomicUsize = AtomicUsize::new(0);
+static DMA7_CHANNEL: AtomicBool = AtomicBool::new(false);
+static DMA8_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[IrqHandler(DMA3)]
+fn dma3_handler() {
+  DMA3_CALLS.fetch_add(1, Ordering::SeqCst);
+  sim::clear_dma(3);
+}
+
+#[IrqHandler(DMA7_8, Channel7)]
+fn dma7_handler(channel: Option<IsrSender<u32>>) {
+  DMA7_CALLS.fetch_add(1, Ordering::SeqCst);
+  DMA7_CHANNEL.store(channel.is_some(), Ordering::SeqCst);
+  sim::clear_dma(7);
+}
+
+#[IrqHandler(DMA7_8, Channel8)]
+fn dma8_handler() {
+  DMA8_CALLS.fetch_add(1, Ordering::SeqCst);
+  sim::clear_dma(8);
+}
+
+#[test]
+fn dispatch_channel_with_own_line() {
+  let _guard = setup();
+  let calls = DMA3_CALLS.load(Ordering::SeqCst);
+
+  activate_dma(3, None);
+  assert!(sim::is_active(Interrupt::DMA3));
+  sim::raise_dma(3);
+  dispatch_irq();
+  assert_eq!(DMA3_CALLS.load(Ordering::SeqCst), calls + 1);
+  // the interrupt has been cleared by the handler
+  dispatch_irq();
+  assert_eq!(DMA3_CALLS.load(Ordering::SeqCst), calls + 1);
+
+  deactivate_dma(3);
+  assert!(!sim::is_active(Interrupt::DMA3));
+}
+
+#[test]
+fn dispatch_shared_line_to_the_channel_handler() {
+  let _guard = setup();
+  let dma7_calls = DMA7_CALLS.load(Ordering::SeqCst);
+  let dma8_calls = DMA8_CALLS.load(Ordering::SeqCst);
+
+  activate_dma(7, channel::<u32>());
+  activate_dma(8, None);
+  sim::raise_dma(8);
+  dispatch_irq();
+  assert_eq!(DMA7_CALLS.load(Ordering::SeqCst), dma7_calls);
+  assert_eq!(DMA8_CALLS.load(Ordering::SeqCst), dma8_calls + 1);
+
+  sim::raise_dma(7);
+  sim::raise_dma(8);
+  dispatch_irq();
+  assert_eq!(DMA7_CALLS.load(Ordering::SeqCst), dma7_calls + 1);
+  assert!(DMA7_CHANNEL.load(Ordering::SeqCst));
+  assert_eq!(DMA8_CALLS.load(Ordering::SeqCst), dma8_calls + 2);
+
+  deactivate_dma(7);
+  deactivate_dma(8);
+}
+
+#[test]
+fn shared_line_dispatches_only_activated_channels() {
+  let _guard = setup();
+  let dma7_calls = DMA7_CALLS.load(Ordering::SeqCst);
+  let dma8_calls = DMA8_CALLS.load(Ordering::SeqCst);
+
+  activate_dma(8, None);
+  sim::raise_dma(7);
+  sim::raise_dma(8);
+  dispatch_irq();
+  assert_eq!(DMA7_CALLS.load(Ordering::SeqCst), dma7_calls);
+  assert_eq!(DMA8_CALLS.load(Ordering::SeqCst), dma8_calls + 1);
+
+  sim::clear_dma(7);
+  deactivate_dma(8);
+}
+
+#[test]
+fn shared_line_stays_active_while_any_channel_is_active() {
+  let _guard = setup();
+
+  activate_dma(7, None);
+  activate_dma(8, None);
+  deactivate_dma(8);
+  assert!(sim::is_active(Interrupt::DMA7_8));
+
+  deactivate_dma(7);
+  assert!(!sim::is_active(Interrupt::DMA7_8));
+}
+
+#[test]
+#[should_panic]
+fn activate_shared_line_without_channel_panics() {
+  let _guard = setup();
+  activate(Interrupt::DMA9_10, None);
+}
+
+#[test]
+#[should_panic]
+fn activate_unknown_channel_panics() {
+  let _guard = setup();
+  activate_dma(15, None);
+}
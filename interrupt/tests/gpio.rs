@@ -0,0 +1,159 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # GPIO pin interrupt tests
+//!
+//! The tests of the dispatching of the GPIO interrupt lines to the handler of the individual pins. They need to be
+//! executed on the host with e.g. ``cargo test --features pi3,sim --target x86_64-unknown-linux-gnu``.
+//!
+
+#![cfg(feature = "sim")]
+
+mod common;
+
+use common::*;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use ruspiro_interrupt::*;
+
+static PIN17_CALLS: AtomicUsize = AtomicUsize::new(0);
+static PIN17_CHANNEL: AtomicBool = AtomicBool::new(false);
+static PIN17_EVENT: AtomicBool = AtomicBool::new(true);
+static PIN30_CALLS: AtomicUsize = AtomicUsize::new(0);
+static BANK1_CALLS: AtomicUsize = AtomicUsize::new(0);
+static PIN53_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[IrqHandler(GpioBank0, Pin17)]
+fn pin17_handler(channel: Option<IsrSender<u8>>) {
+  PIN17_CALLS.fetch_add(1, Ordering::SeqCst);
+  PIN17_CHANNEL.store(channel.is_some(), Ordering::SeqCst);
+  PIN17_EVENT.store(sim::is_gpio_event(17), Ordering::SeqCst);
+}
+
+#[IrqHandler(GpioBank1, Pin30)]
+fn pin30_handler() {
+  PIN30_CALLS.fetch_add(1, Ordering::SeqCst);
+}
+
+/// The handler of the events of the pins of bank 1 without an individual handler
+#[IrqHandler(GpioBank1)]
+fn bank1_handler() {
+  BANK1_CALLS.fetch_add(1, Ordering::SeqCst);
+  sim::clear_gpio(31);
+}
+
+#[IrqHandler(GpioBank2, Pin53)]
+fn pin53_handler() {
+  PIN53_CALLS.fetch_add(1, Ordering::SeqCst);
+}
+
+#[test]
+fn activate_and_deactivate_pin() {
+  let _guard = setup();
+
+  activate_gpio_pin(17, GpioEdge::Falling, None);
+  assert_eq!(sim::gpio_edge(17), Some(GpioEdge::Falling));
+  assert!(sim::is_active(Interrupt::GpioBank0));
+
+  // activating the pin again replaces the event detection
+  activate_gpio_pin(17, GpioEdge::AsyncBoth, None);
+  assert_eq!(sim::gpio_edge(17), Some(GpioEdge::AsyncBoth));
+
+  deactivate_gpio_pin(17);
+  assert_eq!(sim::gpio_edge(17), None);
+  assert!(!sim::is_active(Interrupt::GpioBank0));
+}
+
+#[test]
+fn dispatch_event_to_the_pin_handler() {
+  let _guard = setup();
+  let calls = PIN17_CALLS.load(Ordering::SeqCst);
+
+  activate_gpio_pin(17, GpioEdge::Rising, channel::<u8>());
+  sim::raise_gpio(17);
+  dispatch_irq();
+
+  assert_eq!(PIN17_CALLS.load(Ordering::SeqCst), calls + 1);
+  assert!(PIN17_CHANNEL.load(Ordering::SeqCst));
+  // the event is acknowledged before the handler is called
+  assert!(!PIN17_EVENT.load(Ordering::SeqCst));
+
+  dispatch_irq();
+  assert_eq!(PIN17_CALLS.load(Ordering::SeqCst), calls + 1);
+
+  deactivate_gpio_pin(17);
+}
+
+#[test]
+fn dispatch_events_of_other_pins_to_the_bank_handler() {
+  let _guard = setup();
+  let pin_calls = PIN30_CALLS.load(Ordering::SeqCst);
+  let bank_calls = BANK1_CALLS.load(Ordering::SeqCst);
+
+  activate_gpio_pin(30, GpioEdge::High, None);
+  sim::raise_gpio(30);
+  sim::raise_gpio(31);
+  dispatch_irq();
+
+  assert_eq!(PIN30_CALLS.load(Ordering::SeqCst), pin_calls + 1);
+  assert_eq!(BANK1_CALLS.load(Ordering::SeqCst), bank_calls + 1);
+  assert!(!sim::is_gpio_event(30));
+  assert!(!sim::is_gpio_event(31));
+
+  // the bank handler is not called if only activated pins have an event
+  sim::raise_gpio(30);
+  dispatch_irq();
+  assert_eq!(PIN30_CALLS.load(Ordering::SeqCst), pin_calls + 2);
+  assert_eq!(BANK1_CALLS.load(Ordering::SeqCst), bank_calls + 1);
+
+  deactivate_gpio_pin(30);
+}
+
+#[test]
+fn bank_stays_active_while_any_pin_is_active() {
+  let _guard = setup();
+
+  activate_gpio_pin(46, GpioEdge::Low, None);
+  activate_gpio_pin(53, GpioEdge::Both, None);
+  deactivate_gpio_pin(46);
+  assert!(sim::is_active(Interrupt::GpioBank2));
+
+  let calls = PIN53_CALLS.load(Ordering::SeqCst);
+  sim::raise_gpio(53);
+  dispatch_irq();
+  assert_eq!(PIN53_CALLS.load(Ordering::SeqCst), calls + 1);
+
+  deactivate_gpio_pin(53);
+  assert!(!sim::is_active(Interrupt::GpioBank2));
+}
+
+#[test]
+fn deactivating_the_bank_deactivates_the_pins() {
+  let _guard = setup();
+  let calls = PIN17_CALLS.load(Ordering::SeqCst);
+
+  activate_gpio_pin(17, GpioEdge::Rising, channel::<u8>());
+  // deactivating the line, e.g. as storm quarantine, disables the event detection of the pins
+  deactivate(Interrupt::GpioBank0);
+  assert_eq!(sim::gpio_edge(17), None);
+  assert!(!sim::is_active(Interrupt::GpioBank0));
+
+  // the bank could be activated for the handler of the whole bank without activating the pin again
+  activate(Interrupt::GpioBank0, None);
+  sim::raise_gpio(17);
+  dispatch_irq();
+  assert_eq!(PIN17_CALLS.load(Ordering::SeqCst), calls);
+
+  sim::clear_gpio(17);
+  deactivate(Interrupt::GpioBank0);
+}
+
+#[test]
+#[should_panic]
+fn activate_unknown_pin_panics() {
+  let _guard = setup();
+  activate_gpio_pin(60, GpioEdge::Rising, None);
+}
@@ -0,0 +1,164 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # Inter-processor interrupt tests
+//!
+//! The tests of sending messages between the cores with inter-processor interrupts. They need to be executed on the
+//! host with e.g. ``cargo test --features pi3,sim --target x86_64-unknown-linux-gnu``.
+//!
+
+#![cfg(feature = "sim")]
+
+mod common;
+
+use common::*;
+use core::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use ruspiro_interrupt::*;
+
+static CORE2_CALLS: AtomicUsize = AtomicUsize::new(0);
+/// The content of the mailbox while the handler is running
+static CORE2_MAILBOX: AtomicU32 = AtomicU32::new(0);
+
+#[IrqHandler(Core2Mailbox3)]
+fn core2_mailbox_handler() {
+  CORE2_CALLS.fetch_add(1, Ordering::SeqCst);
+  CORE2_MAILBOX.store(sim::mailbox(2, 3), Ordering::SeqCst);
+}
+
+#[test]
+fn deliver_the_message_to_the_channel_of_the_core() {
+  let _guard = setup();
+  let (tx, rx) = isr_channel::<u32>();
+  activate(Interrupt::Core1Mailbox3, tx);
+
+  // messages sent before the core handles the interrupt are combined
+  send_ipi(1, 0x4);
+  send_ipi(1, 0x1);
+  assert_eq!(sim::mailbox(1, 3), 0x5);
+
+  dispatch_irq_on_core(1);
+  assert!(matches!(rx.recv(), Ok(0x5)));
+  assert!(rx.recv().is_err());
+  assert_eq!(sim::mailbox(1, 3), 0);
+
+  // the cleared mailbox does not raise the interrupt again
+  let spurious = spurious_count(RoutingMode::Irq);
+  dispatch_irq_on_core(1);
+  assert_eq!(spurious_count(RoutingMode::Irq), spurious + 1);
+  assert!(rx.recv().is_err());
+}
+
+#[test]
+fn message_is_only_received_by_the_target_core() {
+  let _guard = setup();
+
+  send_ipi(3, 0x1);
+  dispatch_irq_on_core(0);
+  assert_eq!(sim::mailbox(3, 3), 0x1);
+
+  dispatch_irq_on_core(3);
+  assert_eq!(sim::mailbox(3, 3), 0);
+}
+
+#[test]
+fn handler_is_called_after_the_mailbox_is_cleared() {
+  let _guard = setup();
+  let calls = CORE2_CALLS.load(Ordering::SeqCst);
+
+  send_ipi(2, 0x80);
+  dispatch_irq_on_core(2);
+  assert_eq!(CORE2_CALLS.load(Ordering::SeqCst), calls + 1);
+  assert_eq!(CORE2_MAILBOX.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn message_without_handler_is_not_unhandled() {
+  let _guard = setup();
+  let unhandled = unhandled_count();
+
+  send_ipi(3, 0x1);
+  dispatch_irq_on_core(3);
+  assert_eq!(unhandled_count(), unhandled);
+}
+
+#[test]
+fn mailbox_interrupt_is_raised_once_activated() {
+  let _guard = setup();
+  let (tx, rx) = isr_channel::<u32>();
+
+  send_mailbox_ipi(2, 1, 0x3);
+  dispatch_irq_on_core(2);
+  assert_eq!(sim::mailbox(2, 1), 0x3);
+
+  activate(Interrupt::Core2Mailbox1, tx);
+  assert!(sim::is_active(Interrupt::Core2Mailbox1));
+  dispatch_irq_on_core(2);
+  assert!(matches!(rx.recv(), Ok(0x3)));
+  assert_eq!(sim::mailbox(2, 1), 0);
+
+  deactivate(Interrupt::Core2Mailbox1);
+  assert!(!sim::is_active(Interrupt::Core2Mailbox1));
+}
+
+#[test]
+fn mailboxes_deliver_their_messages_independently() {
+  let _guard = setup();
+  let (reschedule_tx, reschedule_rx) = isr_channel::<u32>();
+  let (halt_tx, halt_rx) = isr_channel::<u32>();
+  activate(Interrupt::Core1Mailbox0, reschedule_tx);
+  activate(Interrupt::Core1Mailbox2, halt_tx);
+
+  send_mailbox_ipi(1, 0, 0x1);
+  send_mailbox_ipi(1, 2, 0x2);
+  dispatch_irq_on_core(1);
+  assert!(matches!(reschedule_rx.recv(), Ok(0x1)));
+  assert!(matches!(halt_rx.recv(), Ok(0x2)));
+  assert_eq!(sim::mailbox(1, 0), 0);
+  assert_eq!(sim::mailbox(1, 2), 0);
+}
+
+#[test]
+#[should_panic]
+fn send_to_unknown_mailbox_panics() {
+  let _guard = setup();
+  send_mailbox_ipi(0, 4, 0x1);
+}
+
+#[test]
+#[should_panic]
+fn send_to_unknown_core_panics() {
+  let _guard = setup();
+  send_ipi(4, 0x1);
+}
+
+#[test]
+#[should_panic]
+fn send_reserved_bit_panics() {
+  let _guard = setup();
+  send_ipi(1, 1 << 31);
+}
+
+#[test]
+fn reserved_bit_could_be_sent_to_other_mailboxes() {
+  let _guard = setup();
+  let (tx, rx) = isr_channel::<u32>();
+  activate(Interrupt::Core1Mailbox0, tx);
+
+  send_mailbox_ipi(1, 0, 1 << 31);
+  dispatch_irq_on_core(1);
+  assert!(matches!(rx.recv(), Ok(0x8000_0000)));
+}
+
+#[test]
+#[should_panic]
+#[cfg(feature = "no_alloc")]
+fn activate_mailbox_with_static_channel_panics() {
+  static CHANNEL: StaticIsrChannel<u32, 4> = StaticIsrChannel::new(OverflowPolicy::DropNewest);
+  let _guard = setup();
+  let (tx, _) = static_isr_channel(&CHANNEL);
+  activate(Interrupt::Core1Mailbox3, tx);
+}
@@ -0,0 +1,114 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # Local timer tests
+//!
+//! The tests of configuring and routing the ARM local timer and dispatching it's interrupt. They need to be executed on
+//! the host with e.g. ``cargo test --features pi3,sim --target x86_64-unknown-linux-gnu``.
+//!
+
+#![cfg(feature = "sim")]
+
+mod common;
+
+use common::*;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use ruspiro_interrupt::*;
+
+static IRQ_CALLS: AtomicUsize = AtomicUsize::new(0);
+static FIQ_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[IrqHandler(LocalTimer)]
+fn local_timer_handler() {
+  IRQ_CALLS.fetch_add(1, Ordering::SeqCst);
+}
+
+#[FiqHandler(LocalTimer)]
+fn local_timer_fiq_handler() {
+  FIQ_CALLS.fetch_add(1, Ordering::SeqCst);
+}
+
+#[test]
+fn configure_the_timer() {
+  let _guard = setup();
+
+  set_local_timer_reload(0x0FFF_FFFF);
+  assert_eq!(sim::local_timer_reload(), 0x0FFF_FFFF);
+  set_local_timer_reload(3_840_000);
+  assert_eq!(sim::local_timer_reload(), 3_840_000);
+
+  start_local_timer();
+  assert!(sim::is_local_timer_running());
+  stop_local_timer();
+  assert!(!sim::is_local_timer_running());
+}
+
+#[test]
+fn interrupt_is_acknowledged_before_the_handler() {
+  let _guard = setup();
+  let calls = IRQ_CALLS.load(Ordering::SeqCst);
+
+  activate(Interrupt::LocalTimer, None);
+  sim::raise(Interrupt::LocalTimer);
+  dispatch_irq_on_core(0);
+  assert_eq!(IRQ_CALLS.load(Ordering::SeqCst), calls + 1);
+
+  // the handler does not acknowledge the interrupt itself
+  dispatch_irq_on_core(0);
+  assert_eq!(IRQ_CALLS.load(Ordering::SeqCst), calls + 1);
+}
+
+#[test]
+fn route_to_another_core() {
+  let _guard = setup();
+  let calls = IRQ_CALLS.load(Ordering::SeqCst);
+
+  route_local_timer(2, RoutingMode::Irq);
+  assert!(sim::local_timer_routing() == (2, RoutingMode::Irq));
+  activate(Interrupt::LocalTimer, None);
+  assert!(sim::is_active_on_core(Interrupt::LocalTimer, 2));
+
+  sim::raise(Interrupt::LocalTimer);
+  dispatch_irq_on_core(0);
+  assert_eq!(IRQ_CALLS.load(Ordering::SeqCst), calls);
+  dispatch_irq_on_core(2);
+  assert_eq!(IRQ_CALLS.load(Ordering::SeqCst), calls + 1);
+}
+
+#[test]
+fn route_as_fiq() {
+  let _guard = setup();
+  let irq_calls = IRQ_CALLS.load(Ordering::SeqCst);
+  let fiq_calls = FIQ_CALLS.load(Ordering::SeqCst);
+
+  activate(Interrupt::LocalTimer, None);
+  // the activated interrupt is moved to the new routing
+  route_local_timer(1, RoutingMode::Fiq);
+  assert!(!sim::is_active(Interrupt::LocalTimer));
+
+  sim::raise(Interrupt::LocalTimer);
+  dispatch_irq_on_core(1);
+  assert_eq!(IRQ_CALLS.load(Ordering::SeqCst), irq_calls);
+  dispatch_fiq_on_core(1);
+  assert_eq!(FIQ_CALLS.load(Ordering::SeqCst), fiq_calls + 1);
+  dispatch_fiq_on_core(1);
+  assert_eq!(FIQ_CALLS.load(Ordering::SeqCst), fiq_calls + 1);
+}
+
+#[test]
+#[should_panic]
+fn reload_value_exceeding_the_counter_panics() {
+  let _guard = setup();
+  set_local_timer_reload(0x1000_0000);
+}
+
+#[test]
+#[should_panic]
+fn route_to_unknown_core_panics() {
+  let _guard = setup();
+  route_local_timer(4, RoutingMode::Irq);
+}
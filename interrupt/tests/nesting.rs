@@ -0,0 +1,222 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # Software interrupt nesting tests
+//!
+//! The tests of the software interrupt nesting of the `nesting` feature. They need to be executed on the host with e.g.
+//! ``cargo test --features pi3,sim,nesting --target x86_64-unknown-linux-gnu``.
+//!
+//! The simulation does not raise an exception if an interrupt becomes pending while IRQ's are enabled. The preemption
+//! of a handler is simulated by calling ``__isr_default`` from within the handler.
+//!
+
+#![cfg(all(feature = "sim", feature = "nesting"))]
+
+mod common;
+
+use common::*;
+use core::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+use ruspiro_interrupt::*;
+
+/// The interrupts in the order their handler has been called
+static CALLS: [AtomicU8; 4] = [
+  AtomicU8::new(0),
+  AtomicU8::new(0),
+  AtomicU8::new(0),
+  AtomicU8::new(0),
+];
+static CALL_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Whether the low priority handler shall simulate the preemption by the high priority interrupt
+static PREEMPT: AtomicBool = AtomicBool::new(false);
+/// Whether the low priority handler shall deactivate it's interrupt
+static DEACTIVATE: AtomicBool = AtomicBool::new(false);
+static LOW_IRQ_ENABLED: AtomicBool = AtomicBool::new(false);
+static LOW_ACTIVE: AtomicBool = AtomicBool::new(false);
+static HIGH_ACTIVE: AtomicBool = AtomicBool::new(false);
+static LOCAL_CALLS: AtomicUsize = AtomicUsize::new(0);
+static LOCAL_IRQ_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// The handler of the low priority interrupt
+#[IrqHandler(SystemTimer3)]
+fn low_priority_handler() {
+  record_call(Interrupt::SystemTimer3);
+  LOW_IRQ_ENABLED.store(is_irq_enabled(), Ordering::SeqCst);
+  LOW_ACTIVE.store(sim::is_active(Interrupt::SystemTimer3), Ordering::SeqCst);
+  HIGH_ACTIVE.store(sim::is_active(Interrupt::Pl011), Ordering::SeqCst);
+  if PREEMPT.load(Ordering::SeqCst) {
+    sim::raise(Interrupt::Pl011);
+    // the preempting interrupt overwrites the exception state
+    sim::take_exception(0x8_0000, 0x3C5);
+    dispatch_irq();
+  }
+  if DEACTIVATE.load(Ordering::SeqCst) {
+    deactivate(Interrupt::SystemTimer3);
+  }
+  sim::clear(Interrupt::SystemTimer3);
+}
+
+/// The handler of the high priority interrupt
+#[IrqHandler(Pl011)]
+fn high_priority_handler() {
+  record_call(Interrupt::Pl011);
+  sim::clear(Interrupt::Pl011);
+}
+
+#[IrqHandler(CntPnsIrq)]
+fn core_timer_handler() {
+  LOCAL_CALLS.fetch_add(1, Ordering::SeqCst);
+  LOCAL_IRQ_ENABLED.store(is_irq_enabled(), Ordering::SeqCst);
+  sim::clear(Interrupt::CntPnsIrq);
+}
+
+fn record_call(irq: Interrupt) {
+  let call = CALL_COUNT.fetch_add(1, Ordering::SeqCst);
+  CALLS[call].store(irq as u8, Ordering::SeqCst);
+}
+
+/// The interrupts in the order their handler has been called since the test started
+fn calls() -> Vec<u8> {
+  CALLS[..CALL_COUNT.load(Ordering::SeqCst)]
+    .iter()
+    .map(|call| call.load(Ordering::SeqCst))
+    .collect()
+}
+
+/// Resets the simulated preemption once the test has finished, before the serialization of the tests is released
+struct NestingGuard {
+  _serial: common::SerialGuard,
+}
+
+impl Drop for NestingGuard {
+  fn drop(&mut self) {
+    PREEMPT.store(false, Ordering::SeqCst);
+    DEACTIVATE.store(false, Ordering::SeqCst);
+  }
+}
+
+/// Serialize the tests and start each one with the two prioritized interrupts activated
+fn setup() -> NestingGuard {
+  let guard = common::setup();
+  CALL_COUNT.store(0, Ordering::SeqCst);
+  set_priority(Interrupt::SystemTimer3, 1);
+  set_priority(Interrupt::Pl011, 10);
+  activate(Interrupt::SystemTimer3, None);
+  activate(Interrupt::Pl011, None);
+  NestingGuard { _serial: guard }
+}
+
+#[test]
+fn dispatch_by_priority() {
+  let _guard = setup();
+  assert_eq!(priority(Interrupt::Pl011), 10);
+
+  sim::raise(Interrupt::SystemTimer3);
+  sim::raise(Interrupt::Pl011);
+  dispatch_irq();
+
+  assert_eq!(
+    calls(),
+    [Interrupt::Pl011 as u8, Interrupt::SystemTimer3 as u8]
+  );
+}
+
+#[test]
+fn higher_priority_preempts_the_handler() {
+  let _guard = setup();
+  PREEMPT.store(true, Ordering::SeqCst);
+
+  sim::raise(Interrupt::SystemTimer3);
+  dispatch_irq();
+
+  assert_eq!(
+    calls(),
+    [Interrupt::SystemTimer3 as u8, Interrupt::Pl011 as u8]
+  );
+  // while the handler was running IRQ's were enabled and only the interrupt of higher priority was active
+  assert!(LOW_IRQ_ENABLED.load(Ordering::SeqCst));
+  assert!(!LOW_ACTIVE.load(Ordering::SeqCst));
+  assert!(HIGH_ACTIVE.load(Ordering::SeqCst));
+  // once the handler returned the state is restored
+  assert!(!is_irq_enabled());
+  assert!(sim::is_active(Interrupt::SystemTimer3));
+  assert!(sim::is_active(Interrupt::Pl011));
+}
+
+#[test]
+fn equal_priority_does_not_preempt() {
+  let _guard = setup();
+  set_priority(Interrupt::Pl011, 1);
+
+  sim::raise(Interrupt::SystemTimer3);
+  dispatch_irq();
+
+  assert!(!HIGH_ACTIVE.load(Ordering::SeqCst));
+  assert!(sim::is_active(Interrupt::Pl011));
+}
+
+#[test]
+fn interrupt_deactivated_by_the_handler_stays_deactivated() {
+  let _guard = setup();
+  DEACTIVATE.store(true, Ordering::SeqCst);
+
+  sim::raise(Interrupt::SystemTimer3);
+  dispatch_irq();
+
+  assert!(!sim::is_active(Interrupt::SystemTimer3));
+  assert!(sim::is_active(Interrupt::Pl011));
+}
+
+#[test]
+fn core_specific_interrupts_are_not_nested() {
+  let _guard = setup();
+  let calls = LOCAL_CALLS.load(Ordering::SeqCst);
+
+  activate(Interrupt::CntPnsIrq, None);
+  sim::raise(Interrupt::CntPnsIrq);
+  dispatch_irq();
+
+  assert_eq!(LOCAL_CALLS.load(Ordering::SeqCst), calls + 1);
+  assert!(!LOCAL_IRQ_ENABLED.load(Ordering::SeqCst));
+  deactivate(Interrupt::CntPnsIrq);
+}
+
+#[test]
+fn preempted_handler_restores_the_exception_state() {
+  let _guard = setup();
+  PREEMPT.store(true, Ordering::SeqCst);
+
+  sim::take_exception(0x1_0000, 0x3C5);
+  sim::raise(Interrupt::SystemTimer3);
+  dispatch_irq();
+
+  assert_eq!(
+    calls(),
+    [Interrupt::SystemTimer3 as u8, Interrupt::Pl011 as u8]
+  );
+  assert_eq!(sim::exception_return(), (0x1_0000, 0x3C5));
+}
+
+#[test]
+fn core_specific_interrupts_are_dispatched_once_if_a_handler_is_preempted() {
+  let _guard = setup();
+  let calls_before = LOCAL_CALLS.load(Ordering::SeqCst);
+  PREEMPT.store(true, Ordering::SeqCst);
+
+  // the core timer interrupt has a lower priority than the GPU interrupt but is dispatched first
+  activate(Interrupt::CntPnsIrq, None);
+  sim::raise(Interrupt::SystemTimer3);
+  sim::raise(Interrupt::CntPnsIrq);
+  dispatch_irq();
+
+  assert_eq!(LOCAL_CALLS.load(Ordering::SeqCst), calls_before + 1);
+  assert_eq!(
+    calls(),
+    [Interrupt::SystemTimer3 as u8, Interrupt::Pl011 as u8]
+  );
+  deactivate(Interrupt::CntPnsIrq);
+}
@@ -0,0 +1,150 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # Remote function call tests
+//!
+//! The tests of executing functions on other cores with inter-processor interrupts. They need to be executed on the
+//! host with e.g. ``cargo test --features pi3,sim --target x86_64-unknown-linux-gnu``.
+//!
+
+#![cfg(feature = "sim")]
+
+mod common;
+
+use common::*;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use ruspiro_interrupt::*;
+use std::sync::Arc;
+
+/// Create a counter and a function incrementing it
+fn counting() -> (Arc<AtomicUsize>, impl Fn() + Send + Sync + 'static) {
+  let calls = Arc::new(AtomicUsize::new(0));
+  let counter = calls.clone();
+  (calls, move || {
+    counter.fetch_add(1, Ordering::SeqCst);
+  })
+}
+
+#[test]
+fn function_is_executed_on_the_target_core() {
+  let _guard = setup();
+  let (calls, function) = counting();
+
+  call_on_core(1, function, None).unwrap();
+  assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+  dispatch_irq_on_core(2);
+  assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+  dispatch_irq_on_core(1);
+  assert_eq!(calls.load(Ordering::SeqCst), 1);
+  assert_eq!(sim::mailbox(1, 3), 0);
+}
+
+#[test]
+fn function_for_the_current_core_is_executed_right_away() {
+  let _guard = setup();
+  let (calls, function) = counting();
+
+  assert_eq!(call_on_core(0, function, Some(0)), Ok(()));
+  assert_eq!(calls.load(Ordering::SeqCst), 1);
+  assert_eq!(sim::mailbox(0, 3), 0);
+}
+
+#[test]
+fn function_is_executed_on_all_cores() {
+  let _guard = setup();
+  let (calls, function) = counting();
+
+  call_on_all_cores(function, None).unwrap();
+  assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+  for core in 1..4 {
+    dispatch_irq_on_core(core);
+  }
+  assert_eq!(calls.load(Ordering::SeqCst), 4);
+}
+
+#[test]
+fn remote_call_is_not_passed_to_the_channel() {
+  let _guard = setup();
+  let (calls, function) = counting();
+  let (tx, rx) = isr_channel::<u32>();
+  activate(Interrupt::Core3Mailbox3, tx);
+
+  call_on_core(3, function, None).unwrap();
+  dispatch_irq_on_core(3);
+  assert_eq!(calls.load(Ordering::SeqCst), 1);
+  assert!(rx.recv().is_err());
+
+  // a message sent together with the remote call is still received
+  send_ipi(3, 0x2);
+  call_on_core(3, || (), None).unwrap();
+  dispatch_irq_on_core(3);
+  assert!(matches!(rx.recv(), Ok(0x2)));
+}
+
+#[test]
+fn wait_for_the_completion_of_the_call() {
+  let _guard = setup();
+  let (calls, function) = counting();
+
+  let caller = std::thread::spawn(move || call_on_core(1, function, Some(1_000)));
+  // the call is dispatched once it has been queued
+  while sim::mailbox(1, 3) == 0 {
+    std::thread::yield_now();
+  }
+  dispatch_irq_on_core(1);
+  assert_eq!(caller.join().unwrap(), Ok(()));
+  assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn wait_for_all_cores_times_out() {
+  let _guard = setup();
+  let (calls, function) = counting();
+
+  let caller = std::thread::spawn(move || call_on_all_cores(function, Some(1_000)));
+  while sim::mailbox(3, 3) == 0 {
+    std::thread::yield_now();
+  }
+  dispatch_irq_on_core(2);
+  // the cores 1 and 3 do not handle their interrupt within the timeout
+  sim::advance_counter(1_000);
+  assert_eq!(
+    caller.join().unwrap(),
+    Err(RemoteCallTimeout { pending: 2 })
+  );
+  assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+  // the calls stay queued for the cores
+  dispatch_irq_on_core(1);
+  dispatch_irq_on_core(3);
+  assert_eq!(calls.load(Ordering::SeqCst), 4);
+}
+
+#[test]
+fn cores_signalled_are_counted_from_any_core() {
+  let _guard = setup();
+  let (calls, function) = counting();
+
+  sim::set_core(2);
+  assert_eq!(
+    call_on_all_cores(function, Some(0)),
+    Err(RemoteCallTimeout { pending: 3 })
+  );
+  sim::set_core(0);
+  assert_eq!(calls.load(Ordering::SeqCst), 1);
+  assert_eq!(sim::mailbox(2, 3), 0);
+}
+
+#[test]
+#[should_panic]
+fn call_on_unknown_core_panics() {
+  let _guard = setup();
+  let _ = call_on_core(4, || (), None);
+}
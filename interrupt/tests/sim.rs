@@ -0,0 +1,475 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # Interrupt dispatching tests
+//!
+//! The tests run the interrupt handling against the simulated interrupt controller of the `sim` feature. They need to
+//! be executed on the host with e.g. ``cargo test --features pi3,sim --target x86_64-unknown-linux-gnu``.
+//!
+//! The simulated interrupt controller replaces the register access of the legacy interrupt controller. The mapping of
+//! it's pending registers to the interrupt banks is therefore not tested here.
+//!
+
+#![cfg(feature = "sim")]
+
+mod common;
+
+use common::*;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use ruspiro_interrupt::*;
+
+static ARM_TIMER_CALLS: AtomicUsize = AtomicUsize::new(0);
+static ARM_TIMER_CHANNEL: AtomicBool = AtomicBool::new(false);
+static PL011_CALLS: AtomicUsize = AtomicUsize::new(0);
+static ARM_MAILBOX_CALLS: AtomicUsize = AtomicUsize::new(0);
+static CNTPNS_CALLS: AtomicUsize = AtomicUsize::new(0);
+static CNTPNS_CHANNEL: AtomicBool = AtomicBool::new(false);
+static CORE1_MAILBOX_CALLS: AtomicUsize = AtomicUsize::new(0);
+static UART1_CALLS: AtomicUsize = AtomicUsize::new(0);
+static UART1_CHANNEL: AtomicBool = AtomicBool::new(false);
+static SPI1_CALLS: AtomicUsize = AtomicUsize::new(0);
+static SPI2_CALLS: AtomicUsize = AtomicUsize::new(0);
+static PIXEL_VALVE_CALLS: AtomicUsize = AtomicUsize::new(0);
+static HDMI_CALLS: AtomicUsize = AtomicUsize::new(0);
+static SYSTEM_TIMER_FIQ_CALLS: AtomicUsize = AtomicUsize::new(0);
+static SYSTEM_TIMER_IRQ_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[IrqHandler(ArmTimer)]
+fn arm_timer_handler(channel: Option<IsrSender<u8>>) {
+  ARM_TIMER_CALLS.fetch_add(1, Ordering::SeqCst);
+  ARM_TIMER_CHANNEL.store(channel.is_some(), Ordering::SeqCst);
+  sim::clear(Interrupt::ArmTimer);
+}
+
+#[IrqHandler(Pl011)]
+fn pl011_handler() {
+  PL011_CALLS.fetch_add(1, Ordering::SeqCst);
+  sim::clear(Interrupt::Pl011);
+}
+
+#[IrqHandler(ArmMailbox)]
+fn arm_mailbox_handler(_channel: Option<IsrSender<()>>) {
+  ARM_MAILBOX_CALLS.fetch_add(1, Ordering::SeqCst);
+}
+
+#[IrqHandler(CntPnsIrq)]
+fn cntpns_handler(channel: Option<IsrSender<u32>>) {
+  CNTPNS_CALLS.fetch_add(1, Ordering::SeqCst);
+  CNTPNS_CHANNEL.store(channel.is_some(), Ordering::SeqCst);
+}
+
+#[IrqHandler(Core1Mailbox3)]
+fn core1_mailbox_handler(_channel: Option<IsrSender<()>>) {
+  CORE1_MAILBOX_CALLS.fetch_add(1, Ordering::SeqCst);
+}
+
+#[IrqHandler(Aux, Uart1)]
+fn aux_uart1_handler(channel: Option<IsrSender<()>>) {
+  UART1_CALLS.fetch_add(1, Ordering::SeqCst);
+  UART1_CHANNEL.store(channel.is_some(), Ordering::SeqCst);
+}
+
+#[IrqHandler(Aux, Spi1)]
+fn aux_spi1_handler(_channel: Option<IsrSender<()>>) {
+  SPI1_CALLS.fetch_add(1, Ordering::SeqCst);
+}
+
+#[IrqHandler(Aux, Spi2)]
+fn aux_spi2_handler(_channel: Option<IsrSender<()>>) {
+  SPI2_CALLS.fetch_add(1, Ordering::SeqCst);
+}
+
+#[IrqHandler(Pwa0)]
+fn pixel_valve_handler() {
+  PIXEL_VALVE_CALLS.fetch_add(1, Ordering::SeqCst);
+  sim::clear(Interrupt::Pwa0);
+}
+
+#[IrqHandler(Hdmi0)]
+fn hdmi_handler() {
+  HDMI_CALLS.fetch_add(1, Ordering::SeqCst);
+  sim::clear(Interrupt::Hdmi0);
+}
+
+#[IrqHandler(SystemTimer1)]
+fn system_timer_irq_handler(_channel: Option<IsrSender<()>>) {
+  SYSTEM_TIMER_IRQ_CALLS.fetch_add(1, Ordering::SeqCst);
+}
+
+#[FiqHandler(SystemTimer1)]
+fn system_timer_fiq_handler(_channel: Option<IsrSender<()>>) {
+  SYSTEM_TIMER_FIQ_CALLS.fetch_add(1, Ordering::SeqCst);
+}
+
+#[test]
+fn activate_and_deactivate() {
+  let _guard = setup();
+  assert!(!sim::is_active(Interrupt::ArmTimer));
+
+  activate(Interrupt::ArmTimer, None);
+  assert!(sim::is_active(Interrupt::ArmTimer));
+  assert!(!sim::is_active(Interrupt::Pl011));
+
+  deactivate(Interrupt::ArmTimer);
+  assert!(!sim::is_active(Interrupt::ArmTimer));
+}
+
+#[test]
+fn initialize_enables_mailbox3_of_all_cores() {
+  let _guard = setup();
+  assert!(sim::is_active(Interrupt::Core0Mailbox3));
+  assert!(sim::is_active(Interrupt::Core1Mailbox3));
+  assert!(sim::is_active(Interrupt::Core2Mailbox3));
+  assert!(sim::is_active(Interrupt::Core3Mailbox3));
+}
+
+#[test]
+fn dispatch_active_interrupt_with_channel() {
+  let _guard = setup();
+  let calls = ARM_TIMER_CALLS.load(Ordering::SeqCst);
+
+  activate(Interrupt::ArmTimer, channel::<u8>());
+  sim::raise(Interrupt::ArmTimer);
+  dispatch_irq();
+
+  assert_eq!(ARM_TIMER_CALLS.load(Ordering::SeqCst), calls + 1);
+  assert!(ARM_TIMER_CHANNEL.load(Ordering::SeqCst));
+
+  // the handler has acknowledged the interrupt, so there is nothing to dispatch any more
+  dispatch_irq();
+  assert_eq!(ARM_TIMER_CALLS.load(Ordering::SeqCst), calls + 1);
+
+  deactivate(Interrupt::ArmTimer);
+}
+
+#[test]
+fn channel_of_other_type_is_not_passed() {
+  let _guard = setup();
+  let calls = ARM_TIMER_CALLS.load(Ordering::SeqCst);
+
+  activate(Interrupt::ArmTimer, channel::<u32>());
+  sim::raise(Interrupt::ArmTimer);
+  dispatch_irq();
+
+  assert_eq!(ARM_TIMER_CALLS.load(Ordering::SeqCst), calls + 1);
+  assert!(!ARM_TIMER_CHANNEL.load(Ordering::SeqCst));
+
+  deactivate(Interrupt::ArmTimer);
+}
+
+#[test]
+fn deactivated_interrupt_is_not_dispatched() {
+  let _guard = setup();
+  let calls = ARM_TIMER_CALLS.load(Ordering::SeqCst);
+
+  activate(Interrupt::ArmTimer, channel::<u8>());
+  deactivate(Interrupt::ArmTimer);
+  sim::raise(Interrupt::ArmTimer);
+  dispatch_irq();
+  assert_eq!(ARM_TIMER_CALLS.load(Ordering::SeqCst), calls);
+
+  // the channel is released with the deactivation
+  activate(Interrupt::ArmTimer, None);
+  dispatch_irq();
+  assert_eq!(ARM_TIMER_CALLS.load(Ordering::SeqCst), calls + 1);
+  assert!(!ARM_TIMER_CHANNEL.load(Ordering::SeqCst));
+
+  deactivate(Interrupt::ArmTimer);
+}
+
+#[test]
+fn dispatch_interrupts_of_all_banks() {
+  let _guard = setup();
+  let pl011_calls = PL011_CALLS.load(Ordering::SeqCst);
+  let mailbox_calls = ARM_MAILBOX_CALLS.load(Ordering::SeqCst);
+  let timer_calls = ARM_TIMER_CALLS.load(Ordering::SeqCst);
+
+  activate(Interrupt::Pl011, None);
+  activate(Interrupt::ArmMailbox, None);
+  activate(Interrupt::ArmTimer, None);
+  sim::raise(Interrupt::Pl011);
+  sim::raise(Interrupt::ArmMailbox);
+  dispatch_irq();
+
+  assert_eq!(PL011_CALLS.load(Ordering::SeqCst), pl011_calls + 1);
+  assert_eq!(ARM_MAILBOX_CALLS.load(Ordering::SeqCst), mailbox_calls + 1);
+  assert_eq!(ARM_TIMER_CALLS.load(Ordering::SeqCst), timer_calls);
+
+  deactivate(Interrupt::Pl011);
+  deactivate(Interrupt::ArmMailbox);
+  deactivate(Interrupt::ArmTimer);
+}
+
+#[test]
+fn dispatch_display_interrupts() {
+  let _guard = setup();
+  let pixel_valve_calls = PIXEL_VALVE_CALLS.load(Ordering::SeqCst);
+  let hdmi_calls = HDMI_CALLS.load(Ordering::SeqCst);
+
+  activate(Interrupt::Pwa0, None);
+  activate(Interrupt::Hdmi0, None);
+  sim::raise(Interrupt::Pwa0);
+  dispatch_irq();
+  assert_eq!(PIXEL_VALVE_CALLS.load(Ordering::SeqCst), pixel_valve_calls + 1);
+  assert_eq!(HDMI_CALLS.load(Ordering::SeqCst), hdmi_calls);
+
+  sim::raise(Interrupt::Hdmi0);
+  dispatch_irq();
+  assert_eq!(PIXEL_VALVE_CALLS.load(Ordering::SeqCst), pixel_valve_calls + 1);
+  assert_eq!(HDMI_CALLS.load(Ordering::SeqCst), hdmi_calls + 1);
+
+  deactivate(Interrupt::Pwa0);
+  deactivate(Interrupt::Hdmi0);
+}
+
+#[test]
+#[should_panic]
+fn activate_aux_without_device_panics() {
+  let _guard = setup();
+  activate(Interrupt::Aux, None);
+}
+
+#[test]
+fn dispatch_aux_to_the_device_handler() {
+  let _guard = setup();
+  let uart1_calls = UART1_CALLS.load(Ordering::SeqCst);
+  let spi1_calls = SPI1_CALLS.load(Ordering::SeqCst);
+  let spi2_calls = SPI2_CALLS.load(Ordering::SeqCst);
+
+  activate_aux(AuxDevice::Spi1, None);
+  sim::raise_aux(AuxDevice::Spi1);
+  dispatch_irq();
+  assert_eq!(UART1_CALLS.load(Ordering::SeqCst), uart1_calls);
+  assert_eq!(SPI1_CALLS.load(Ordering::SeqCst), spi1_calls + 1);
+  assert_eq!(SPI2_CALLS.load(Ordering::SeqCst), spi2_calls);
+
+  sim::clear_aux(AuxDevice::Spi1);
+  activate_aux(AuxDevice::Uart1, None);
+  activate_aux(AuxDevice::Spi2, None);
+  sim::raise_aux(AuxDevice::Uart1);
+  sim::raise_aux(AuxDevice::Spi2);
+  dispatch_irq();
+  assert_eq!(UART1_CALLS.load(Ordering::SeqCst), uart1_calls + 1);
+  assert_eq!(SPI1_CALLS.load(Ordering::SeqCst), spi1_calls + 1);
+  assert_eq!(SPI2_CALLS.load(Ordering::SeqCst), spi2_calls + 1);
+
+  deactivate(Interrupt::Aux);
+}
+
+#[test]
+fn aux_device_channels_are_released_with_the_line() {
+  let _guard = setup();
+
+  activate_aux(AuxDevice::Uart1, channel::<()>());
+  activate_aux(AuxDevice::Spi1, None);
+  sim::raise_aux(AuxDevice::Uart1);
+  dispatch_irq();
+  assert!(UART1_CHANNEL.load(Ordering::SeqCst));
+
+  deactivate(Interrupt::Aux);
+  activate_aux(AuxDevice::Spi1, None);
+  let calls = UART1_CALLS.load(Ordering::SeqCst);
+  dispatch_irq();
+  // the released device is no longer passed it's pending interrupt
+  assert_eq!(UART1_CALLS.load(Ordering::SeqCst), calls);
+
+  activate_aux(AuxDevice::Uart1, None);
+  dispatch_irq();
+  assert!(!UART1_CHANNEL.load(Ordering::SeqCst));
+
+  deactivate(Interrupt::Aux);
+}
+
+#[test]
+fn aux_device_not_activated_is_not_dispatched() {
+  let _guard = setup();
+  let uart1_calls = UART1_CALLS.load(Ordering::SeqCst);
+  let spi1_calls = SPI1_CALLS.load(Ordering::SeqCst);
+
+  activate_aux(AuxDevice::Spi1, None);
+  // the uart raises it's interrupt while only the SPI1 keeps the line active
+  sim::raise_aux(AuxDevice::Uart1);
+  sim::raise_aux(AuxDevice::Spi1);
+  dispatch_irq();
+  assert_eq!(UART1_CALLS.load(Ordering::SeqCst), uart1_calls);
+  assert_eq!(SPI1_CALLS.load(Ordering::SeqCst), spi1_calls + 1);
+
+  deactivate(Interrupt::Aux);
+}
+
+#[test]
+fn aux_line_stays_active_while_any_device_is_active() {
+  let _guard = setup();
+
+  activate_aux(AuxDevice::Uart1, None);
+  activate_aux(AuxDevice::Spi2, None);
+  deactivate_aux(AuxDevice::Uart1);
+  assert!(sim::is_active(Interrupt::Aux));
+
+  deactivate_aux(AuxDevice::Spi2);
+  assert!(!sim::is_active(Interrupt::Aux));
+}
+
+#[test]
+fn deactivated_aux_device_releases_it_channel() {
+  let _guard = setup();
+
+  activate_aux(AuxDevice::Uart1, channel::<()>());
+  activate_aux(AuxDevice::Spi1, None);
+  deactivate_aux(AuxDevice::Uart1);
+  sim::raise_aux(AuxDevice::Uart1);
+  dispatch_irq();
+  assert!(!UART1_CHANNEL.load(Ordering::SeqCst));
+
+  deactivate_aux(AuxDevice::Spi1);
+}
+
+#[test]
+#[should_panic]
+fn activate_aux_as_fiq_panics() {
+  let _guard = setup();
+  activate_fiq(Interrupt::Aux, None);
+}
+
+#[test]
+fn dispatch_core_timer_on_the_activated_core() {
+  let _guard = setup();
+  let calls = CNTPNS_CALLS.load(Ordering::SeqCst);
+
+  activate_on_core(Interrupt::CntPnsIrq, 2, channel::<u32>());
+  assert!(sim::is_active_on_core(Interrupt::CntPnsIrq, 2));
+  assert!(!sim::is_active_on_core(Interrupt::CntPnsIrq, 0));
+
+  // the timer raised on a core it is not activated for is not dispatched
+  sim::raise_on_core(Interrupt::CntPnsIrq, 0);
+  dispatch_irq();
+  assert_eq!(CNTPNS_CALLS.load(Ordering::SeqCst), calls);
+
+  sim::set_core(2);
+  sim::raise_on_core(Interrupt::CntPnsIrq, 2);
+  dispatch_irq();
+  assert_eq!(CNTPNS_CALLS.load(Ordering::SeqCst), calls + 1);
+  assert!(CNTPNS_CHANNEL.load(Ordering::SeqCst));
+
+  // the channel of core 0 is used on core 0
+  activate_on_core(Interrupt::CntPnsIrq, 0, None);
+  sim::set_core(0);
+  dispatch_irq();
+  assert_eq!(CNTPNS_CALLS.load(Ordering::SeqCst), calls + 2);
+  assert!(!CNTPNS_CHANNEL.load(Ordering::SeqCst));
+
+  deactivate_on_core(Interrupt::CntPnsIrq, 2);
+  deactivate_on_core(Interrupt::CntPnsIrq, 0);
+  assert!(!sim::is_active_on_core(Interrupt::CntPnsIrq, 2));
+}
+
+#[test]
+fn dispatch_mailbox_on_the_receiving_core() {
+  let _guard = setup();
+  let calls = CORE1_MAILBOX_CALLS.load(Ordering::SeqCst);
+
+  sim::raise(Interrupt::Core1Mailbox3);
+  dispatch_irq();
+  assert_eq!(CORE1_MAILBOX_CALLS.load(Ordering::SeqCst), calls);
+
+  sim::set_core(1);
+  dispatch_irq();
+  assert_eq!(CORE1_MAILBOX_CALLS.load(Ordering::SeqCst), calls + 1);
+}
+
+#[test]
+fn dispatch_gpu_interrupts_on_the_routed_core() {
+  let _guard = setup();
+  let calls = PL011_CALLS.load(Ordering::SeqCst);
+
+  activate(Interrupt::Pl011, None);
+  route_gpu_irqs(3, RoutingMode::Irq);
+  assert_eq!(sim::gpu_routing(RoutingMode::Irq), 3);
+
+  sim::raise(Interrupt::Pl011);
+  dispatch_irq();
+  assert_eq!(PL011_CALLS.load(Ordering::SeqCst), calls);
+
+  sim::set_core(3);
+  dispatch_irq();
+  assert_eq!(PL011_CALLS.load(Ordering::SeqCst), calls + 1);
+
+  deactivate(Interrupt::Pl011);
+}
+
+#[test]
+fn dispatch_fiq_to_the_fiq_handler() {
+  let _guard = setup();
+  let fiq_calls = SYSTEM_TIMER_FIQ_CALLS.load(Ordering::SeqCst);
+  let irq_calls = SYSTEM_TIMER_IRQ_CALLS.load(Ordering::SeqCst);
+
+  activate_fiq(Interrupt::SystemTimer1, None);
+  assert!(sim::is_fiq_active(Interrupt::SystemTimer1));
+  assert!(!sim::is_active(Interrupt::SystemTimer1));
+
+  sim::raise(Interrupt::SystemTimer1);
+  dispatch_irq();
+  dispatch_fiq();
+  assert_eq!(SYSTEM_TIMER_FIQ_CALLS.load(Ordering::SeqCst), fiq_calls + 1);
+  assert_eq!(SYSTEM_TIMER_IRQ_CALLS.load(Ordering::SeqCst), irq_calls);
+
+  deactivate_fiq(Interrupt::SystemTimer1);
+  assert!(!sim::is_fiq_active(Interrupt::SystemTimer1));
+  dispatch_fiq();
+  assert_eq!(SYSTEM_TIMER_FIQ_CALLS.load(Ordering::SeqCst), fiq_calls + 1);
+}
+
+#[test]
+fn registered_handler_takes_precedence() {
+  let _guard = setup();
+  let timer_calls = ARM_TIMER_CALLS.load(Ordering::SeqCst);
+  let calls = std::sync::Arc::new(AtomicUsize::new(0));
+
+  let handler_calls = calls.clone();
+  register_handler(
+    Interrupt::ArmTimer,
+    Box::new(move || {
+      handler_calls.fetch_add(1, Ordering::SeqCst);
+    }),
+  );
+  activate(Interrupt::ArmTimer, None);
+  sim::raise(Interrupt::ArmTimer);
+  dispatch_irq();
+  assert_eq!(calls.load(Ordering::SeqCst), 1);
+  assert_eq!(ARM_TIMER_CALLS.load(Ordering::SeqCst), timer_calls);
+
+  // once unregistered the link time handler is the fallback again
+  unregister_handler(Interrupt::ArmTimer);
+  dispatch_irq();
+  assert_eq!(calls.load(Ordering::SeqCst), 1);
+  assert_eq!(ARM_TIMER_CALLS.load(Ordering::SeqCst), timer_calls + 1);
+
+  deactivate(Interrupt::ArmTimer);
+}
+
+#[test]
+fn registered_handler_without_link_time_handler() {
+  let _guard = setup();
+  let calls = std::sync::Arc::new(AtomicUsize::new(0));
+
+  let handler_calls = calls.clone();
+  register_handler(
+    Interrupt::Usb,
+    Box::new(move || {
+      handler_calls.fetch_add(1, Ordering::SeqCst);
+      sim::clear(Interrupt::Usb);
+    }),
+  );
+  activate_fiq(Interrupt::Usb, None);
+  sim::raise(Interrupt::Usb);
+  dispatch_fiq();
+  dispatch_fiq();
+  assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+  deactivate_fiq(Interrupt::Usb);
+  unregister_handler(Interrupt::Usb);
+}
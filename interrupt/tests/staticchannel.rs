@@ -0,0 +1,128 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # Static ISR channel tests
+//!
+//! The tests of the allocation free channel of the `no_alloc` feature. They need to be executed on the host with e.g.
+//! ``cargo test --features pi3,sim,no_alloc --target x86_64-unknown-linux-gnu``.
+//!
+
+#![cfg(all(feature = "sim", feature = "no_alloc"))]
+
+mod common;
+
+use common::*;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use ruspiro_interrupt::*;
+
+#[IrqHandler(SystemTimer3)]
+fn system_timer_handler(channel: Option<StaticIsrSender<u32, 4>>) {
+  if let Some(tx) = channel {
+    tx.send(3);
+  }
+  sim::clear(Interrupt::SystemTimer3);
+}
+
+#[test]
+fn receive_in_send_order() {
+  static CHANNEL: StaticIsrChannel<u32, 4> = StaticIsrChannel::new(OverflowPolicy::DropNewest);
+  let (tx, rx) = static_isr_channel(&CHANNEL);
+  for round in 0..3 {
+    for value in 0..4 {
+      tx.send(round * 10 + value);
+    }
+    for value in 0..4 {
+      assert_eq!(rx.recv(), Some(round * 10 + value));
+    }
+    assert_eq!(rx.recv(), None);
+  }
+}
+
+#[test]
+fn drop_newest_if_full() {
+  static CHANNEL: StaticIsrChannel<u32, 2> = StaticIsrChannel::new(OverflowPolicy::DropNewest);
+  let (tx, rx) = static_isr_channel(&CHANNEL);
+  for value in 0..4 {
+    tx.send(value);
+  }
+  assert_eq!(rx.recv(), Some(0));
+  assert_eq!(rx.recv(), Some(1));
+  assert_eq!(rx.recv(), None);
+  assert_eq!(rx.dropped(), 0);
+}
+
+#[test]
+fn drop_oldest_if_full() {
+  static CHANNEL: StaticIsrChannel<u32, 2> = StaticIsrChannel::new(OverflowPolicy::DropOldest);
+  let (tx, rx) = static_isr_channel(&CHANNEL);
+  for value in 0..5 {
+    tx.send(value);
+  }
+  assert_eq!(rx.recv(), Some(3));
+  assert_eq!(rx.recv(), Some(4));
+  assert_eq!(rx.recv(), None);
+}
+
+#[test]
+fn count_dropped_if_full() {
+  static CHANNEL: StaticIsrChannel<u32, 2> = StaticIsrChannel::new(OverflowPolicy::CountAndDrop);
+  let (tx, rx) = static_isr_channel(&CHANNEL);
+  for value in 0..5 {
+    tx.send(value);
+  }
+  assert_eq!(rx.dropped(), 3);
+  assert_eq!(rx.recv(), Some(0));
+  assert_eq!(rx.recv(), Some(1));
+  assert_eq!(rx.recv(), None);
+}
+
+#[test]
+fn receive_from_multiple_senders() {
+  static CHANNEL: StaticIsrChannel<usize, 8> = StaticIsrChannel::new(OverflowPolicy::CountAndDrop);
+  static FINISHED: AtomicUsize = AtomicUsize::new(0);
+  let (tx, rx) = static_isr_channel(&CHANNEL);
+  for sender in 0..4 {
+    std::thread::spawn(move || {
+      for value in 0..1000 {
+        tx.send(sender * 1000 + value);
+      }
+      FINISHED.fetch_add(1, Ordering::SeqCst);
+    });
+  }
+
+  // the data of each sender is received in the order it has been send
+  let mut last = [None; 4];
+  let mut received = 0;
+  let mut receive = |data: usize| {
+    let sender = data / 1000;
+    assert!(last[sender] < Some(data));
+    last[sender] = Some(data);
+    received += 1;
+  };
+  while FINISHED.load(Ordering::SeqCst) < 4 {
+    if let Some(data) = rx.recv() {
+      receive(data);
+    }
+  }
+  while let Some(data) = rx.recv() {
+    receive(data);
+  }
+  assert_eq!(received + rx.dropped(), 4000);
+}
+
+#[test]
+fn send_from_the_interrupt_handler() {
+  static CHANNEL: StaticIsrChannel<u32, 4> = StaticIsrChannel::new(OverflowPolicy::DropNewest);
+  let (tx, rx) = static_isr_channel(&CHANNEL);
+  let _guard = setup();
+  activate(Interrupt::SystemTimer3, tx);
+  sim::raise(Interrupt::SystemTimer3);
+  dispatch_irq();
+
+  assert_eq!(rx.recv(), Some(3));
+  assert_eq!(rx.recv(), None);
+}
@@ -0,0 +1,98 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # Interrupt statistics tests
+//!
+//! The tests of the interrupt statistics of the `stats` feature. They need to be executed on the host with e.g.
+//! ``cargo test --features pi3,sim,stats --target x86_64-unknown-linux-gnu``.
+//!
+
+#![cfg(all(feature = "sim", feature = "stats"))]
+
+mod common;
+
+use common::*;
+use core::sync::atomic::{AtomicU64, Ordering};
+use ruspiro_interrupt::*;
+
+/// The ticks the handler of the ``Pl011`` interrupt takes
+static PL011_TICKS: AtomicU64 = AtomicU64::new(0);
+
+#[IrqHandler(Pl011)]
+fn pl011_handler() {
+  sim::advance_counter(PL011_TICKS.load(Ordering::SeqCst));
+  sim::clear(Interrupt::Pl011);
+}
+
+#[FiqHandler(SystemTimer1)]
+fn system_timer_fiq_handler() {
+  sim::advance_counter(5);
+  sim::clear(Interrupt::SystemTimer1);
+}
+
+#[test]
+fn count_and_time_the_handler_calls() {
+  let _guard = setup();
+  assert_eq!(stats(Interrupt::Pl011), IrqStats::default());
+
+  activate(Interrupt::Pl011, None);
+  sim::set_counter(100);
+  PL011_TICKS.store(10, Ordering::SeqCst);
+  sim::raise(Interrupt::Pl011);
+  dispatch_irq();
+
+  sim::set_counter(200);
+  PL011_TICKS.store(30, Ordering::SeqCst);
+  sim::raise(Interrupt::Pl011);
+  dispatch_irq();
+
+  // the handler has acknowledged the interrupt, so it is not counted again
+  dispatch_irq();
+
+  assert_eq!(
+    stats(Interrupt::Pl011),
+    IrqStats {
+      count: 2,
+      last_fired: 200,
+      max_duration: 30,
+      avg_duration: 20,
+    }
+  );
+  deactivate(Interrupt::Pl011);
+}
+
+#[test]
+fn count_the_fiq_handler_calls() {
+  let _guard = setup();
+
+  activate_fiq(Interrupt::SystemTimer1, None);
+  sim::set_counter(50);
+  sim::raise(Interrupt::SystemTimer1);
+  dispatch_fiq();
+
+  let timer = stats(Interrupt::SystemTimer1);
+  assert_eq!(timer.count, 1);
+  assert_eq!(timer.last_fired, 50);
+  assert_eq!(timer.max_duration, 5);
+  deactivate_fiq(Interrupt::SystemTimer1);
+}
+
+#[test]
+fn iterate_the_interrupts() {
+  let _guard = setup();
+
+  activate(Interrupt::ArmTimer, None);
+  sim::raise(Interrupt::ArmTimer);
+  dispatch_irq();
+  deactivate(Interrupt::ArmTimer);
+
+  assert!(stats_iter().any(|(irq, stats)| irq == Interrupt::ArmTimer && stats.count == 1));
+  assert!(stats_iter().all(|(irq, stats)| irq == Interrupt::Pl011
+    || irq == Interrupt::SystemTimer1
+    || irq == Interrupt::ArmTimer
+    || stats == IrqStats::default()));
+}
@@ -0,0 +1,165 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # Interrupt storm detection tests
+//!
+//! The tests of the interrupt storm detection. They need to be executed on the host with e.g.
+//! ``cargo test --features pi3,sim --target x86_64-unknown-linux-gnu``.
+//!
+
+#![cfg(feature = "sim")]
+
+mod common;
+
+use common::*;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use ruspiro_interrupt::*;
+
+static PL011_CALLS: AtomicUsize = AtomicUsize::new(0);
+static SYSTEM_TIMER_FIQ_CALLS: AtomicUsize = AtomicUsize::new(0);
+static CNTPNS_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+/// The handler never acknowledges the interrupt which causes an interrupt storm
+#[IrqHandler(Pl011)]
+fn pl011_handler() {
+  PL011_CALLS.fetch_add(1, Ordering::SeqCst);
+}
+
+#[FiqHandler(SystemTimer1)]
+fn system_timer_fiq_handler() {
+  SYSTEM_TIMER_FIQ_CALLS.fetch_add(1, Ordering::SeqCst);
+}
+
+#[IrqHandler(CntPnsIrq)]
+fn cntpns_handler() {
+  CNTPNS_CALLS.fetch_add(1, Ordering::SeqCst);
+}
+
+/// Disables the storm detection once the test has finished, before the serialization of the tests is released
+struct StormGuard {
+  _serial: common::SerialGuard,
+}
+
+impl Drop for StormGuard {
+  fn drop(&mut self) {
+    disable_storm_detection();
+    clear_storm_handler();
+  }
+}
+
+/// Serialize the tests and start each one with a freshly initialized interrupt controller
+fn setup() -> StormGuard {
+  StormGuard {
+    _serial: common::setup(),
+  }
+}
+
+#[test]
+fn quarantine_interrupt_storm() {
+  let _guard = setup();
+  let calls = PL011_CALLS.load(Ordering::SeqCst);
+  let storms = std::sync::Arc::new(AtomicUsize::new(0));
+
+  let storm_handler_calls = storms.clone();
+  set_storm_handler(Box::new(move |irq| {
+    assert!(irq == Interrupt::Pl011);
+    storm_handler_calls.fetch_add(1, Ordering::SeqCst);
+  }));
+  configure_storm_detection(3, 100);
+  activate(Interrupt::Pl011, None);
+  sim::raise(Interrupt::Pl011);
+  for _ in 0..5 {
+    dispatch_irq();
+  }
+
+  assert_eq!(PL011_CALLS.load(Ordering::SeqCst), calls + 3);
+  assert_eq!(storms.load(Ordering::SeqCst), 1);
+  assert!(is_quarantined(Interrupt::Pl011));
+  assert!(!sim::is_active(Interrupt::Pl011));
+
+  // the activation releases the interrupt from quarantine
+  activate(Interrupt::Pl011, None);
+  assert!(!is_quarantined(Interrupt::Pl011));
+  dispatch_irq();
+  assert_eq!(PL011_CALLS.load(Ordering::SeqCst), calls + 4);
+
+  deactivate(Interrupt::Pl011);
+}
+
+#[test]
+fn dispatches_in_a_new_window_are_no_storm() {
+  let _guard = setup();
+  let calls = PL011_CALLS.load(Ordering::SeqCst);
+
+  configure_storm_detection(3, 100);
+  activate(Interrupt::Pl011, None);
+  sim::raise(Interrupt::Pl011);
+  for _ in 0..10 {
+    dispatch_irq();
+    sim::advance_counter(50);
+  }
+
+  assert_eq!(PL011_CALLS.load(Ordering::SeqCst), calls + 10);
+  assert!(!is_quarantined(Interrupt::Pl011));
+
+  deactivate(Interrupt::Pl011);
+}
+
+#[test]
+fn storm_detection_is_disabled_by_default() {
+  let _guard = setup();
+  let calls = PL011_CALLS.load(Ordering::SeqCst);
+
+  activate(Interrupt::Pl011, None);
+  sim::raise(Interrupt::Pl011);
+  for _ in 0..10 {
+    dispatch_irq();
+  }
+
+  assert_eq!(PL011_CALLS.load(Ordering::SeqCst), calls + 10);
+  assert!(!is_quarantined(Interrupt::Pl011));
+
+  deactivate(Interrupt::Pl011);
+}
+
+#[test]
+fn quarantine_fiq_storm() {
+  let _guard = setup();
+  let calls = SYSTEM_TIMER_FIQ_CALLS.load(Ordering::SeqCst);
+
+  configure_storm_detection(2, 100);
+  activate_fiq(Interrupt::SystemTimer1, None);
+  sim::raise(Interrupt::SystemTimer1);
+  for _ in 0..5 {
+    dispatch_fiq();
+  }
+
+  assert_eq!(SYSTEM_TIMER_FIQ_CALLS.load(Ordering::SeqCst), calls + 2);
+  assert!(is_quarantined(Interrupt::SystemTimer1));
+  assert!(!sim::is_fiq_active(Interrupt::SystemTimer1));
+}
+
+#[test]
+fn quarantine_core_timer_on_the_core_it_storms() {
+  let _guard = setup();
+  let calls = CNTPNS_CALLS.load(Ordering::SeqCst);
+
+  configure_storm_detection(2, 100);
+  activate_on_core(Interrupt::CntPnsIrq, 0, None);
+  activate_on_core(Interrupt::CntPnsIrq, 1, None);
+  sim::set_core(1);
+  sim::raise_on_core(Interrupt::CntPnsIrq, 1);
+  for _ in 0..5 {
+    dispatch_irq();
+  }
+
+  assert_eq!(CNTPNS_CALLS.load(Ordering::SeqCst), calls + 2);
+  assert!(!sim::is_active_on_core(Interrupt::CntPnsIrq, 1));
+  assert!(sim::is_active_on_core(Interrupt::CntPnsIrq, 0));
+
+  deactivate_on_core(Interrupt::CntPnsIrq, 0);
+}
@@ -0,0 +1,151 @@
+/***********************************************************************************************************************
+ * Copyright (c) 2019 by the authors
+ *
+ * Author: André Borrmann
+ * License: Apache License 2.0
+ **********************************************************************************************************************/
+
+//! # Unhandled interrupt tests
+//!
+//! The tests of the reporting of unhandled and spurious interrupts. They need to be executed on the host with e.g.
+//! ``cargo test --features pi3,sim --target x86_64-unknown-linux-gnu``.
+//!
+
+#![cfg(feature = "sim")]
+
+mod common;
+
+use common::*;
+use core::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use ruspiro_interrupt::*;
+
+static UNHANDLED_CALLS: AtomicUsize = AtomicUsize::new(0);
+static UNHANDLED_IRQ: AtomicU32 = AtomicU32::new(0);
+static UNHANDLED_BANK: AtomicU32 = AtomicU32::new(0);
+
+/// Overrides the default hook, acknowledging the unhandled interrupt
+#[no_mangle]
+extern "C" fn unhandled_interrupt(irq_number: u32, bank: u32) {
+  UNHANDLED_CALLS.fetch_add(1, Ordering::SeqCst);
+  UNHANDLED_IRQ.store(irq_number, Ordering::SeqCst);
+  UNHANDLED_BANK.store(bank, Ordering::SeqCst);
+  sim::reset();
+}
+
+#[IrqHandler(Aux, Uart1)]
+fn aux_uart1_handler() {
+  sim::clear_aux(AuxDevice::Uart1);
+}
+
+#[test]
+fn report_interrupt_without_handler() {
+  let _guard = setup();
+  let calls = UNHANDLED_CALLS.load(Ordering::SeqCst);
+  let unhandled = unhandled_count();
+
+  activate(Interrupt::ArmDoorbell0, None);
+  sim::raise(Interrupt::ArmDoorbell0);
+  dispatch_irq();
+
+  assert_eq!(UNHANDLED_CALLS.load(Ordering::SeqCst), calls + 1);
+  assert_eq!(
+    UNHANDLED_IRQ.load(Ordering::SeqCst),
+    Interrupt::ArmDoorbell0 as u32
+  );
+  assert_eq!(UNHANDLED_BANK.load(Ordering::SeqCst), 2);
+  assert_eq!(unhandled_count(), unhandled + 1);
+}
+
+#[test]
+fn report_fiq_without_handler() {
+  let _guard = setup();
+  let calls = UNHANDLED_CALLS.load(Ordering::SeqCst);
+
+  activate_fiq(Interrupt::Isp, None);
+  sim::raise(Interrupt::Isp);
+  dispatch_fiq();
+
+  assert_eq!(UNHANDLED_CALLS.load(Ordering::SeqCst), calls + 1);
+  assert_eq!(UNHANDLED_IRQ.load(Ordering::SeqCst), Interrupt::Isp as u32);
+  assert_eq!(UNHANDLED_BANK.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn report_aux_device_without_handler() {
+  let _guard = setup();
+  let calls = UNHANDLED_CALLS.load(Ordering::SeqCst);
+
+  activate_aux(AuxDevice::Uart1, None);
+  sim::raise_aux(AuxDevice::Uart1);
+  dispatch_irq();
+  assert_eq!(UNHANDLED_CALLS.load(Ordering::SeqCst), calls);
+
+  sim::raise_aux(AuxDevice::Spi2);
+  dispatch_irq();
+  assert_eq!(UNHANDLED_CALLS.load(Ordering::SeqCst), calls + 1);
+  assert_eq!(UNHANDLED_IRQ.load(Ordering::SeqCst), Interrupt::Aux as u32);
+
+  // the activated device without handler is reported the same way
+  activate_aux(AuxDevice::Spi2, None);
+  sim::raise_aux(AuxDevice::Spi2);
+  dispatch_irq();
+  assert_eq!(UNHANDLED_CALLS.load(Ordering::SeqCst), calls + 2);
+}
+
+#[test]
+fn gpu_interrupt_is_not_reported_as_core_interrupt() {
+  let _guard = setup();
+  let calls = UNHANDLED_CALLS.load(Ordering::SeqCst);
+
+  // a pending GPU interrupt sets the GPU bit of the core specific bank as well
+  register_handler(
+    Interrupt::ArmDoorbell1,
+    Box::new(|| sim::clear(Interrupt::ArmDoorbell1)),
+  );
+  activate(Interrupt::ArmDoorbell1, None);
+  sim::raise(Interrupt::ArmDoorbell1);
+  dispatch_irq();
+  assert_eq!(UNHANDLED_CALLS.load(Ordering::SeqCst), calls);
+  deactivate(Interrupt::ArmDoorbell1);
+
+  activate_fiq(Interrupt::ArmDoorbell1, None);
+  sim::raise(Interrupt::ArmDoorbell1);
+  dispatch_fiq();
+  assert_eq!(UNHANDLED_CALLS.load(Ordering::SeqCst), calls);
+  deactivate_fiq(Interrupt::ArmDoorbell1);
+
+  unregister_handler(Interrupt::ArmDoorbell1);
+}
+
+#[test]
+fn registered_handler_is_no_unhandled_interrupt() {
+  let _guard = setup();
+  let calls = UNHANDLED_CALLS.load(Ordering::SeqCst);
+
+  register_handler(
+    Interrupt::ArmDoorbell1,
+    Box::new(|| sim::clear(Interrupt::ArmDoorbell1)),
+  );
+  activate(Interrupt::ArmDoorbell1, None);
+  sim::raise(Interrupt::ArmDoorbell1);
+  dispatch_irq();
+  assert_eq!(UNHANDLED_CALLS.load(Ordering::SeqCst), calls);
+
+  deactivate(Interrupt::ArmDoorbell1);
+  unregister_handler(Interrupt::ArmDoorbell1);
+}
+
+#[test]
+fn count_spurious_interrupts() {
+  let _guard = setup();
+  let spurious_irqs = spurious_count(RoutingMode::Irq);
+  let spurious_fiqs = spurious_count(RoutingMode::Fiq);
+
+  // the interrupt is not activated, so it is not pending for the interrupt handling
+  sim::raise(Interrupt::Pl011);
+  dispatch_irq();
+  assert_eq!(spurious_count(RoutingMode::Irq), spurious_irqs + 1);
+
+  dispatch_fiq();
+  assert_eq!(spurious_count(RoutingMode::Fiq), spurious_fiqs + 1);
+}
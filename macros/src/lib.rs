@@ -9,26 +9,25 @@
 //! # Interrupt Macros
 //!
 //! This crate provides the custom attribute ``#[IrqHandler(<interrupt type>[, <source>])]`` to be used when
-//! implementing an interrupt handler. Detailed documentation can be found in the `ruspiro-interrupt` crate.
+//! implementing an interrupt handler and ``#[FiqHandler(<interrupt type>)]`` to be used when implementing the handler
+//! of an interrupt raised as FIQ. Detailed documentation can be found in the `ruspiro-interrupt` crate.
 //!
 
 extern crate proc_macro;
 extern crate quote;
 extern crate syn;
 
+use core::ops::RangeInclusive;
 use proc_macro::*;
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::*;
 
 #[proc_macro_attribute]
 #[allow(non_snake_case)]
 pub fn IrqHandler(attr: TokenStream, item: TokenStream) -> TokenStream {
-  // indicate usage of this macro in the compiler output
-  println!("implement handler for IRQ: \"{}\"", attr.to_string());
-
   let func = parse_macro_input!(item as ItemFn);
   let args: AttributeArgs = parse_macro_input!(attr as AttributeArgs);
-  let irq_name = match args.get(0) {
+  let irq_name = match args.first() {
     Some(NestedMeta::Meta(Meta::Path(meta))) => &meta.segments.first().unwrap().ident,
     _ => {
       return quote! {
@@ -41,51 +40,84 @@ pub fn IrqHandler(attr: TokenStream, item: TokenStream) -> TokenStream {
   // verify the signature of the function given to the handler
   // the required signature may differ depending on the interrupt that shall be handled
   // first check the basic ones
-  let valid_common_signature = func.sig.constness.is_none()  // no "fn const"
-        && func.vis == Visibility::Inherited        // inherited in the current crate?
-        && func.sig.abi.is_none()                       // no "extern" is used
-        && func.sig.generics.params.is_empty()     // no generics like fn handler<T>
-        && func.sig.generics.where_clause.is_none() // no generics in a where clause like fn handler(a: T) where T: FnOnce
-        && func.sig.variadic.is_none()             // no variadic parameters like fn handler(...)
-        && match func.sig.output {                 // only default return types allowed
-            ReturnType::Default => true,
-            _ => false,
-        };
+  let valid_common_signature = has_valid_common_signature(&func);
 
   let irq_id_s = irq_name.to_string();
   let irq_func_suffix = match &*irq_id_s {
-    "Aux" => {
-      // Aux IrqHandler tag signature is: IrqHandler(Aux,Uart1)
-      let aux_source = match args.get(1) {
-                Some(NestedMeta::Meta(Meta::Path(meta))) => meta,
-                _=> {
-                    return quote! {
-                        compile_error!("`Aux` interrupt source missing in `#[IrqHandler(Aux, <SOURCE>)`. <SOURCE> could be one of: `Uart1` | `Spi1` | `Spi2`.");
-                    }.into()
-                }
-            };
-      let aux_source_s = aux_source.segments.first().unwrap().ident.to_string();
-      // check for valid Aux types
-      if &*aux_source_s != "Uart1" && &*aux_source_s != "Spi1" && &*aux_source_s != "Spi2" {
-        return quote! { compile_error!("Wrong source for `Aux` interrupt in `#[IrqHandler(Aux, <SOURCE>)`. <SOURCE> could be one of: `Uart1` | `Spi1` | `Spi2`.");
+    line if shared_line_sources(line).is_some() => {
+      // shared line IrqHandler tag signature is: IrqHandler(Aux, Uart1)
+      let sources = shared_line_sources(line).unwrap();
+      let wrong_source = format!(
+        "Wrong or missing source for `{}` interrupt in `#[IrqHandler({}, <SOURCE>)`. <SOURCE> could be one of: {}.",
+        line,
+        line,
+        sources
+          .iter()
+          .map(|source| format!("`{}`", source))
+          .collect::<Vec<_>>()
+          .join(" | ")
+      );
+      let source_s = match args.get(1) {
+        Some(NestedMeta::Meta(Meta::Path(meta))) => {
+          meta.segments.first().unwrap().ident.to_string()
+        }
+        _ => return quote! { compile_error!(#wrong_source); }.into(),
+      };
+      if !sources.contains(&&*source_s) {
+        return quote! { compile_error!(#wrong_source); }.into();
+      }
+
+      if !valid_common_signature {
+        return quote! {
+                    compile_error!("interrupt handler must have signature `[unsafe] fn([channel: Option<IsrSender<T>>])`");
                 }.into();
       }
-      let valid_signature = valid_common_signature; // && func.decl.inputs.is_empty();
 
-      if !valid_signature {
+      format!("{}_{}", line, source_s)
+    }
+    bank if gpio_bank_pins(bank).is_some() => {
+      // GPIO pin IrqHandler tag signature is: IrqHandler(GpioBank0, Pin17), without the pin the handler is implemented
+      // for the events of the whole bank
+      let pins = gpio_bank_pins(bank).unwrap();
+      let wrong_pin = format!(
+        "Wrong source for `{}` interrupt in `#[IrqHandler({}, <SOURCE>)`. <SOURCE> could be one of: `Pin{}`..`Pin{}`.",
+        irq_id_s,
+        irq_id_s,
+        pins.start(),
+        pins.end()
+      );
+      let pin = match args.get(1) {
+        None => None,
+        Some(NestedMeta::Meta(Meta::Path(meta))) => {
+          let pin_s = meta.segments.first().unwrap().ident.to_string();
+          match pin_s
+            .strip_prefix("Pin")
+            .and_then(|pin| pin.parse::<u8>().ok())
+          {
+            Some(pin) if pins.contains(&pin) => Some(pin),
+            _ => return quote! { compile_error!(#wrong_pin); }.into(),
+          }
+        }
+        _ => return quote! { compile_error!(#wrong_pin); }.into(),
+      };
+
+      if !valid_common_signature {
         return quote! {
-                    compile_error!("interrupt handler must have signature `[unsafe] fn(tx: IsrSender(Box<dyn Any>))`");
+                    compile_error!("interrupt handler must have signature `[unsafe] fn([channel: Option<IsrSender<T>>])`");
                 }.into();
       }
 
-      format!("{}_{}", irq_name.to_string(), aux_source_s)
+      match pin {
+        Some(pin) => format!("{}_Pin{}", irq_id_s, pin),
+        None => irq_id_s.clone(),
+      }
     }
     _ => {
       let valid_signature = valid_common_signature; // && func.decl.inputs.is_empty();
 
       if !valid_signature {
         return quote! {
-                    compile_error!("interrupt handler must have signature `[unsafe] fn(tx: IsrSender(Box<dyn Any>)))`");
+                    compile_error!("interrupt handler must have signature `[unsafe] fn([channel: Option<IsrSender<T>>])`");
                 }.into();
       }
 
@@ -93,27 +125,194 @@ pub fn IrqHandler(attr: TokenStream, item: TokenStream) -> TokenStream {
     }
   };
 
+  let irq_name_s = format!("__irq_handler__{}", irq_func_suffix);
+  handler_impl(func, irq_name, irq_name_s)
+}
+
+/// The custom attribute ``#[FiqHandler(<interrupt type>)]`` implements the handler of an interrupt that has been
+/// activated to be raised as FIQ. Shared interrupt lines like ``Aux`` can not be handled as FIQ.
+#[proc_macro_attribute]
+#[allow(non_snake_case)]
+pub fn FiqHandler(attr: TokenStream, item: TokenStream) -> TokenStream {
+  let func = parse_macro_input!(item as ItemFn);
+  let args: AttributeArgs = parse_macro_input!(attr as AttributeArgs);
+  let irq_name = match args.first() {
+    Some(NestedMeta::Meta(Meta::Path(meta))) => &meta.segments.first().unwrap().ident,
+    _ => {
+      return quote! {
+          compile_error!("interrupt identifier missing in `#[FiqHandler(identifier)`");
+      }
+      .into();
+    }
+  };
+
+  if shared_line_sources(&irq_name.to_string()).is_some() {
+    let error = format!(
+      "the shared `{}` interrupt can not be handled as FIQ",
+      irq_name
+    );
+    return quote! {
+        compile_error!(#error);
+    }
+    .into();
+  }
+
+  if !has_valid_common_signature(&func) {
+    return quote! {
+        compile_error!("interrupt handler must have signature `[unsafe] fn([channel: Option<IsrSender<T>>])`");
+    }
+    .into();
+  }
+
+  let fiq_name_s = format!("__fiq_handler__{}", irq_name);
+  handler_impl(func, irq_name, fiq_name_s)
+}
+
+/// The interrupt lines shared between several sources that require the source to be given with the
+/// ``#[IrqHandler(<interrupt type>, <source>)]`` attribute. The `ruspiro-interrupt` crate generates the sources of it's
+/// shared lines and their default handlers from this list with [shared_sources] and [shared_line_handlers]. The
+/// position of a source within the list is the bit indicating it in the status of the line.
+const SHARED_LINES: &[(&str, &[&str])] = &[
+  ("Aux", &["Uart1", "Spi1", "Spi2"]),
+  ("DMA7_8", &["Channel7", "Channel8"]),
+  ("DMA9_10", &["Channel9", "Channel10"]),
+];
+
+/// The GPIO banks sharing their interrupt line between their pins. The sources of the line are named after the pins, e.g.
+/// ``Pin17``. Other than the lines in [SHARED_LINES] a handler could also be implemented for the whole bank. It receives
+/// the events of the pins that have not been activated individually.
+const GPIO_BANKS: &[(&str, RangeInclusive<u8>)] = &[
+  ("GpioBank0", 0..=27),
+  ("GpioBank1", 28..=45),
+  ("GpioBank2", 46..=57),
+];
+
+/// Get the pins of the given interrupt if it is the interrupt line of a GPIO bank
+fn gpio_bank_pins(irq_name: &str) -> Option<RangeInclusive<u8>> {
+  GPIO_BANKS
+    .iter()
+    .find(|(bank, _)| *bank == irq_name)
+    .map(|(_, pins)| pins.clone())
+}
+
+/// Get the names of the sources of the given interrupt if it is a shared interrupt line or the line of a GPIO bank
+fn line_sources(irq_name: &str) -> Option<Vec<String>> {
+  shared_line_sources(irq_name)
+    .map(|sources| sources.iter().map(|source| source.to_string()).collect())
+    .or_else(|| {
+      gpio_bank_pins(irq_name).map(|pins| pins.map(|pin| format!("Pin{}", pin)).collect())
+    })
+}
+
+/// Get the sources of the given interrupt if it is a shared interrupt line
+fn shared_line_sources(irq_name: &str) -> Option<&'static [&'static str]> {
+  SHARED_LINES
+    .iter()
+    .find(|(line, _)| *line == irq_name)
+    .map(|(_, sources)| *sources)
+}
+
+/// Generate the sources of the given shared interrupt line or GPIO bank in the order of [SHARED_LINES] or [GPIO_BANKS].
+/// This is only meant to be used by the `ruspiro-interrupt` crate to define the sources of it's ``SharedLine``'s, so they can't get out of sync with
+/// the sources accepted by the ``#[IrqHandler(<interrupt type>, <source>)]`` attribute.
+#[doc(hidden)]
+#[proc_macro]
+pub fn shared_sources(item: TokenStream) -> TokenStream {
+  let line = parse_macro_input!(item as syn::Ident);
+  let sources = match line_sources(&line.to_string()) {
+    Some(sources) => sources,
+    None => {
+      let error = format!("`{}` is not a shared interrupt line", line);
+      return quote! { compile_error!(#error); }.into();
+    }
+  };
+  let handlers = sources
+    .iter()
+    .map(|source| format_ident!("__irq_handler__{}_{}", line, source));
+
+  quote!(
+    [#(
+      crate::sharedline::SharedSource {
+        handler: crate::#handlers,
+        channel: crate::isrcell::IsrCell::new(),
+      }
+    ),*]
+  )
+  .into()
+}
+
+/// Generate the default handlers of the sources of all shared interrupt lines and GPIO banks. This is only meant to be used by the
+/// `ruspiro-interrupt` crate where it expands to it's ``default_handler_impl!`` macro.
+#[doc(hidden)]
+#[proc_macro]
+pub fn shared_line_handlers(_item: TokenStream) -> TokenStream {
+  let (sources, lines): (Vec<_>, Vec<_>) = SHARED_LINES
+    .iter()
+    .map(|(line, _)| line)
+    .chain(GPIO_BANKS.iter().map(|(bank, _)| bank))
+    .flat_map(|line| {
+      line_sources(line).unwrap().into_iter().map(move |source| {
+        (
+          format_ident!("{}_{}", line, source),
+          format_ident!("{}", line),
+        )
+      })
+    })
+    .unzip();
+
+  quote!(
+    default_handler_impl![#(#sources => #lines),*];
+  )
+  .into()
+}
+
+/// Check the basic signature requirements of a function implementing an interrupt handler
+fn has_valid_common_signature(func: &ItemFn) -> bool {
+  func.sig.constness.is_none()  // no "fn const"
+        && func.vis == Visibility::Inherited        // inherited in the current crate?
+        && func.sig.abi.is_none()                       // no "extern" is used
+        && func.sig.generics.params.is_empty()     // no generics like fn handler<T>
+        && func.sig.generics.where_clause.is_none() // no generics in a where clause like fn handler(a: T) where T: FnOnce
+        && func.sig.variadic.is_none()             // no variadic parameters like fn handler(...)
+        && match func.sig.output {                 // only default return types allowed
+            ReturnType::Default => true,
+            _ => false,
+        }
+        && func.sig.inputs.len() <= 1              // the channel is the only optional parameter
+        && func.sig.inputs.iter().all(|input| matches!(input, FnArg::Typed(_))) // no self parameter
+}
+
+/// Generate the exported interrupt handler function from the function the attribute is attached to
+fn handler_impl(func: ItemFn, irq_name: &syn::Ident, export_name: String) -> TokenStream {
   let ident = func.sig.ident; // original function identifier
   let attrs = func.attrs; // function attributes #[...]
   let block = func.block; // function block
   let stmts = block.stmts; // function statements
 
-  let irq_name_s = format!("__irq_handler__{}", irq_func_suffix);
-  return quote!(
+  // the channel parameter receives the sender with the type given in the function signature
+  let channel = match func.sig.inputs.first() {
+    Some(FnArg::Typed(PatType { pat, ty, .. })) => quote! {
+      let #pat: #ty = ruspiro_interrupt::isr_sender(channel);
+    },
+    _ => quote! {
+      let _ = channel;
+    },
+  };
+
+  quote!(
     // use a fixed export name to ensure the same irq handler is not implemented twice
     #[allow(non_snake_case)]
-    #[export_name = #irq_name_s]
+    #[export_name = #export_name]
     #(#attrs)*
     #[no_mangle]
-    pub unsafe extern "C" fn #ident(
-      channel: Option<ruspiro_interrupt::IsrSender<crate::alloc::boxed::Box<dyn core::any::Any>>>
-    ) {
+    pub unsafe extern "C" fn #ident(channel: &ruspiro_interrupt::IsrChannel) {
       // force compiler error if the irq_name does not appear in the Interrupt enum that need to be
       // referred to in the crate using this attribute
       ruspiro_interrupt::Interrupt::#irq_name;
 
+      #channel
       #(#stmts)*
     }
   )
-  .into();
+  .into()
 }